@@ -3,7 +3,7 @@ use std::{collections::HashMap, sync::Arc};
 use lsp_types::{
     CompletionItem, CompletionItemKind, CompletionTextEdit, Diagnostic,
     DiagnosticRelatedInformation, DiagnosticSeverity, DocumentSymbol, FoldingRange,
-    FoldingRangeKind, Hover, HoverContents, Location, MarkupContent, MarkupKind,
+    FoldingRangeKind, Hover, HoverContents, Location, MarkupContent, MarkupKind, NumberOrString,
     ParameterInformation, ParameterLabel, Position, PositionEncodingKind, SemanticToken,
     SemanticTokens, SignatureHelp, SignatureInformation, SymbolKind, TextEdit, Url,
 };
@@ -11,7 +11,8 @@ use mmt_rs::diag::{Diagnostic as MmtDiagnostic, Severity};
 use mmt_rs::pack::{PackManifest, PackRegistry};
 use mmt_rs::source::TextRange;
 use mmt_rs::syntax::{
-    DirectiveItemSyntax, SpeakerMarkerSyntax, StatementKind, SyntaxDocument, SyntaxNode,
+    DirectiveItemSyntax, SpeakerMarkerSyntax, StatementKind, StatementSyntax, SyntaxDocument,
+    SyntaxNode,
 };
 use mmt_rs::{
     AnalyzedDocument, DocumentTimezone, EmitOptions, ResolvedResourceKind, SpeakerIdentity,
@@ -284,7 +285,9 @@ impl LanguageService {
                 Severity::Warning => DiagnosticSeverity::WARNING,
                 Severity::Info => DiagnosticSeverity::INFORMATION,
             }),
-            code: None,
+            code: diagnostic
+                .code
+                .map(|code| NumberOrString::String(code.to_string())),
             code_description: None,
             source: Some("mmt".to_string()),
             message: diagnostic.message.clone(),
@@ -298,13 +301,84 @@ impl LanguageService {
         let Some(document) = self.snapshot(uri) else {
             return Vec::new();
         };
-        document
+        let speakers = document
             .analysis
-            .document
-            .nodes
+            .actors
+            .speakers
             .iter()
-            .filter_map(|node| self.symbol(document, node))
-            .collect()
+            .map(|speaker| (speaker.statement_range, &speaker.speaker))
+            .collect::<HashMap<_, _>>();
+        let mut symbols = Vec::new();
+        let mut dialogue = Vec::new();
+        for node in &document.analysis.document.nodes {
+            match node {
+                SyntaxNode::Statement(statement) => dialogue.push(statement),
+                node if matches!(node, SyntaxNode::Blank(_)) || node.is_comment() => {}
+                _ => {
+                    symbols.extend(self.dialogue_symbol(document, &speakers, &dialogue));
+                    dialogue.clear();
+                    symbols.extend(self.symbol(document, node));
+                }
+            }
+        }
+        symbols.extend(self.dialogue_symbol(document, &speakers, &dialogue));
+        symbols
+    }
+
+    /// Groups a run of consecutive statements into one outline entry that
+    /// lists its speakers in order of first appearance.
+    #[allow(deprecated)]
+    fn dialogue_symbol(
+        &self,
+        document: &DocumentSnapshot,
+        resolved: &HashMap<TextRange, &SpeakerIdentity>,
+        statements: &[&StatementSyntax],
+    ) -> Option<DocumentSymbol> {
+        let (first, last) = (statements.first()?, statements.last()?);
+        let actors = &document.analysis.actors;
+        let mut speakers = Vec::<String>::new();
+        for statement in statements {
+            let resolved = resolved
+                .get(&statement.range)
+                .and_then(|speaker| match speaker {
+                    SpeakerIdentity::Actor(actor_id) => actors
+                        .actors
+                        .get(actor_id.0 as usize)
+                        .map(|actor| actor.primary_name.clone()),
+                    SpeakerIdentity::Builtin(builtin) => Some(builtin.display_name().to_string()),
+                });
+            // Without a pack, unresolved explicit markers still name the speaker.
+            let name = resolved.or_else(|| match &statement.marker {
+                Some(SpeakerMarkerSyntax::Explicit { raw, .. }) => Some(raw.clone()),
+                _ => None,
+            });
+            if let Some(name) = name
+                && !speakers.contains(&name)
+            {
+                speakers.push(name);
+            }
+        }
+        let name = if speakers.is_empty() {
+            "narration".to_string()
+        } else {
+            speakers.join(", ")
+        };
+        Some(DocumentSymbol {
+            name,
+            detail: Some(format!("{} message(s)", statements.len())),
+            kind: SymbolKind::STRING,
+            tags: None,
+            deprecated: None,
+            range: document.lines.range(
+                &document.text,
+                TextRange::new(first.range.start, last.range.end),
+                &self.encoding,
+            )?,
+            selection_range: document
+                .lines
+                .range(&document.text, first.range, &self.encoding)?,
+            children: None,
+        })
     }
 
     #[allow(deprecated)]
@@ -546,6 +620,7 @@ impl LanguageService {
                 &[
                     ("@actor", "open or create a script actor"),
                     ("@asset", "declare a script-local asset"),
+                    ("@comment", COMMENT_DIRECTIVE_DESCRIPTION),
                     ("@document", DOCUMENT_DIRECTIVE_DESCRIPTION),
                     ("@mode", "change the following content mode"),
                     ("@typ", TYP_DIRECTIVE_DESCRIPTION),
@@ -610,19 +685,7 @@ impl LanguageService {
             }
         }
 
-        let Some(block) = document
-            .analysis
-            .document
-            .nodes
-            .iter()
-            .find_map(|node| match node {
-                SyntaxNode::DirectiveBlock(block)
-                    if block.range.start <= offset && offset <= block.range.end =>
-                {
-                    Some(block)
-                }
-                _ => None,
-            })
+        let Some(SyntaxNode::DirectiveBlock(block)) = document.analysis.document.node_at(offset)
         else {
             return Vec::new();
         };
@@ -681,6 +744,7 @@ impl LanguageService {
                 ),
                 ("display-name", "name shown by the renderer"),
                 ("avatar", "avatar used by following messages"),
+                ("nickname", "short name for compact renderer UI"),
                 ("also-as", "additional writable names for this actor"),
             ],
             "asset" => &[
@@ -747,8 +811,24 @@ impl LanguageService {
                 .speakers
                 .iter()
                 .find(|speaker| speaker.statement_range == statement.range)?;
-            let SpeakerIdentity::Actor(actor_id) = speaker.speaker else {
-                return None;
+            let range = document
+                .lines
+                .range(&document.text, marker_range, &self.encoding)?;
+            let actor_id = match &speaker.speaker {
+                SpeakerIdentity::Actor(actor_id) => *actor_id,
+                SpeakerIdentity::Builtin(builtin) => {
+                    return Some(Hover {
+                        contents: HoverContents::Markup(MarkupContent {
+                            kind: MarkupKind::Markdown,
+                            value: format!(
+                                "Built-in speaker {}\n\nReference {} has no current actor on this side",
+                                markdown_code(&builtin.0),
+                                markdown_code(&document.text[marker_range.start..marker_range.end]),
+                            ),
+                        }),
+                        range: Some(range),
+                    });
+                }
             };
             let actor = actors.actors.iter().find(|actor| actor.id == actor_id)?;
             let revision = actor
@@ -762,12 +842,19 @@ impl LanguageService {
                 markdown_code(&actor.preset_id),
                 revision.number,
             );
-            if !matches!(marker, SpeakerMarkerSyntax::Explicit { .. }) {
-                value.push_str(&format!(
+            match marker {
+                SpeakerMarkerSyntax::Explicit { raw, .. } if *raw != actor.primary_name => {
+                    value.push_str(&format!("\n\nMatched alias {}", markdown_code(raw)));
+                }
+                SpeakerMarkerSyntax::Explicit { .. } => {}
+                _ => value.push_str(&format!(
                     "\n\nReference {} → {}",
                     markdown_code(&document.text[marker_range.start..marker_range.end]),
                     markdown_code(&revision.state.display_name),
-                ));
+                )),
+            }
+            if let Some(nickname) = &revision.state.nickname {
+                value.push_str(&format!("\n\nNickname {}", markdown_code(nickname)));
             }
             if let Some(avatar) = &revision.state.avatar {
                 value.push_str(&format!("\n\nAvatar {}", markdown_code(avatar)));
@@ -780,9 +867,6 @@ impl LanguageService {
             {
                 value.push_str(&format!("\n\n![Actor avatar]({url})"));
             }
-            let range = document
-                .lines
-                .range(&document.text, marker_range, &self.encoding)?;
             return Some(Hover {
                 contents: HoverContents::Markup(MarkupContent {
                     kind: MarkupKind::Markdown,
@@ -1466,6 +1550,8 @@ fn markdown_code(value: &str) -> String {
 
 const DOCUMENT_DIRECTIVE_DESCRIPTION: &str =
     "Configure document title, author, title-bar visibility, and compilation time.";
+const COMMENT_DIRECTIVE_DESCRIPTION: &str =
+    "Keep lines up to @end verbatim without rendering them.";
 const TYP_DIRECTIVE_DESCRIPTION: &str = "Insert raw Typst content that is checked with the generated document and mapped back to this source.";
 
 const DOCUMENT_FIELDS: &[(&str, &str)] = &[
@@ -1546,6 +1632,10 @@ fn directive_hover_at(
                 marker_range,
                 format!("**@typ**\n\n{TYP_DIRECTIVE_DESCRIPTION}"),
             )),
+            "comment" => Some((
+                marker_range,
+                format!("**@comment**\n\n{COMMENT_DIRECTIVE_DESCRIPTION}"),
+            )),
             _ => None,
         }
     })
@@ -2003,6 +2093,32 @@ mod tests {
         assert_eq!(service.folding_ranges(&uri()).len(), 2);
     }
 
+    #[test]
+    fn groups_consecutive_statements_into_dialogue_symbols() {
+        let mut service = LanguageService::default();
+        service.open(
+            uri(),
+            1,
+            "- 开场\n\n> 柚子: 你好\n@comment: 草稿\n< 早\n> 再见\n@reply\n- A\n@end\n- 旁白"
+                .to_string(),
+        );
+        let symbols = service.document_symbols(&uri());
+
+        assert_eq!(
+            symbols
+                .iter()
+                .map(|symbol| (symbol.name.as_str(), symbol.detail.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("柚子, Sensei", Some("4 message(s)")),
+                ("@reply", Some("1 item(s)")),
+                ("narration", Some("1 message(s)")),
+            ]
+        );
+        assert_eq!(symbols[0].range.start, Position::new(0, 0));
+        assert_eq!(symbols[0].range.end.line, 5);
+    }
+
     #[test]
     fn maps_diagnostic_labels_to_related_information() {
         let mut service = LanguageService::default();
@@ -2572,6 +2688,8 @@ mod tests {
                 .contains("**小鸟游日富美\\]\\(https://evil\\.test/pixel\\)\\!\\[**")
         );
         assert!(second.value.contains("revision 1"));
+        assert!(!first.value.contains("Matched alias"));
+        assert!(second.value.contains("Matched alias ` hifumi `"));
         assert!(
             second
                 .value
@@ -2585,6 +2703,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn speaker_reference_to_a_builtin_fallback_hovers_the_builtin() {
+        let mut service = LanguageService::default();
+        service.open(uri(), 1, "< _0: 早".to_string());
+
+        let (value, range) = markdown_hover(&service, 0, 2);
+        assert_eq!(
+            value,
+            "Built-in speaker ` __Sensei `\n\nReference ` _0 ` has no current actor on this side"
+        );
+        assert_eq!(range.start, Position::new(0, 2));
+    }
+
     #[test]
     fn speaker_references_complete_and_hover_with_the_current_revision() {
         let manifest = r#"{
@@ -829,10 +829,7 @@ fn build_render_project_generation(
     let projection = project_analyzed_with_pack(
         &document.source,
         &document.analysis,
-        &EmitOptions {
-            timestamp,
-            ..EmitOptions::default()
-        },
+        &EmitOptions::default().with_timestamp(timestamp),
     )?;
     let mut files = EMBEDDED_TEMPLATE_TEXT_FILES
         .iter()
@@ -1343,12 +1340,9 @@ mod tests {
     }
 
     fn catalog() -> StaticPresetCatalog {
-        StaticPresetCatalog::new(vec![mmt_rs::CharacterPreset {
-            id: "ba::柚子".to_string(),
-            names: vec!["柚子".to_string()],
-            display_name: Some("柚子".to_string()),
-            avatar: None,
-        }])
+        StaticPresetCatalog::new(vec![
+            mmt_rs::CharacterPreset::new("ba::柚子", ["柚子"]).with_display_name("柚子"),
+        ])
     }
 
     fn snapshot(
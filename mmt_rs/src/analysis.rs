@@ -1,8 +1,11 @@
 //! Stable JSON analysis surface for editors and WASM hosts.
 
-use serde::Serialize;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
 
 use crate::diag::{Diagnostic, DiagnosticPhase, Severity};
+use crate::identity::derived_key;
 use crate::source::{LineColumn, SourceFile, TextRange};
 use crate::syntax::SyntaxNode;
 
@@ -16,6 +19,10 @@ pub struct AnalysisReport<'a> {
     pub schema: &'static str,
     pub ast: AstReport<'a>,
     pub diagnostics: Vec<AnalysisDiagnostic>,
+    /// Content-derived ids parallel to `ast.nodes`, when requested through
+    /// [`AnalysisOptions::emit_node_ids`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_ids: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -31,6 +38,10 @@ pub struct AnalysisDiagnostic {
     pub message: String,
     pub span: Option<SourceSpan>,
     pub labels: Vec<AnalysisLabel>,
+    /// Raw text of the line the diagnostic starts on, when requested through
+    /// [`AnalysisOptions::capture_source_lines`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_line: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
@@ -46,27 +57,91 @@ pub struct AnalysisLabel {
     pub span: Option<SourceSpan>,
 }
 
+/// Host-side analysis settings. Every setting is off by default, matching
+/// the report [`analyze_text_json`] produces; hosts opt in to each extra
+/// field with its `with_*` setter, or WASM hosts with the matching JSON key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct AnalysisOptions {
+    /// Copies each diagnostic's source line into the report so hosts can show
+    /// it without keeping the source around. Off by default to keep reports
+    /// small.
+    pub capture_source_lines: bool,
+    /// Derives an id for each top-level node from its kind and normalized
+    /// source text, so hosts can key annotations on nodes across edits.
+    pub emit_node_ids: bool,
+    /// Leaves out `parts` of bodies that are a single text part spanning the
+    /// whole body, since `source` and `range` already say the same. Off by
+    /// default so every body has the same shape.
+    pub omit_plain_body_parts: bool,
+}
+
+impl AnalysisOptions {
+    pub fn with_capture_source_lines(mut self, capture_source_lines: bool) -> Self {
+        self.capture_source_lines = capture_source_lines;
+        self
+    }
+
+    pub fn with_emit_node_ids(mut self, emit_node_ids: bool) -> Self {
+        self.emit_node_ids = emit_node_ids;
+        self
+    }
+
+    pub fn with_omit_plain_body_parts(mut self, omit_plain_body_parts: bool) -> Self {
+        self.omit_plain_body_parts = omit_plain_body_parts;
+        self
+    }
+}
+
 pub fn analyze_text_json(text: &str) -> Result<String, serde_json::Error> {
+    analyze_text_json_with_options(text, &AnalysisOptions::default())
+}
+
+pub fn analyze_text_json_with_options(
+    text: &str,
+    options: &AnalysisOptions,
+) -> Result<String, serde_json::Error> {
     let source = SourceFile::anonymous(text);
     let document = crate::parse_document(&source);
     let diagnostics = document
         .diagnostics
         .iter()
-        .map(|diagnostic| analysis_diagnostic(diagnostic, &source))
+        .map(|diagnostic| analysis_diagnostic(diagnostic, &source, options))
         .collect();
-    serde_json::to_string(&AnalysisReport {
+    let report = AnalysisReport {
         schema: ANALYSIS_SCHEMA,
         ast: AstReport {
             range: document.range,
             nodes: &document.nodes,
         },
         diagnostics,
+        node_ids: options
+            .emit_node_ids
+            .then(|| node_ids(&document.nodes, text)),
+    };
+    crate::syntax::omitting_plain_body_parts(options.omit_plain_body_parts, || {
+        serde_json::to_string(&report)
     })
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub fn analyze_text_wasm(text: &str) -> String {
-    match analyze_text_json(text) {
+    wasm_report(analyze_text_json(text))
+}
+
+/// `options_json` is an [`AnalysisOptions`] object such as
+/// `{"emit_node_ids": true}`; omitted keys keep their defaults.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn analyze_text_wasm_with_options(text: &str, options_json: &str) -> String {
+    wasm_report(
+        serde_json::from_str::<AnalysisOptions>(options_json)
+            .and_then(|options| analyze_text_json_with_options(text, &options)),
+    )
+}
+
+fn wasm_report(result: Result<String, serde_json::Error>) -> String {
+    match result {
         Ok(json) => json,
         Err(error) => format!(
             "{{\"schema\":\"{ANALYSIS_SCHEMA}\",\"ast\":null,\"diagnostics\":[{{\"severity\":\"error\",\"phase\":\"syntax\",\"message\":{}}}]}}",
@@ -76,14 +151,19 @@ pub fn analyze_text_wasm(text: &str) -> String {
     }
 }
 
-fn analysis_diagnostic(diagnostic: &Diagnostic, source: &SourceFile) -> AnalysisDiagnostic {
+fn analysis_diagnostic(
+    diagnostic: &Diagnostic,
+    source: &SourceFile,
+    options: &AnalysisOptions,
+) -> AnalysisDiagnostic {
+    let span = diagnostic
+        .range
+        .and_then(|range| source_span(source, range));
     AnalysisDiagnostic {
         severity: diagnostic.severity,
         phase: diagnostic.phase,
         message: diagnostic.message.clone(),
-        span: diagnostic
-            .range
-            .and_then(|range| source_span(source, range)),
+        span,
         labels: diagnostic
             .labels
             .iter()
@@ -92,6 +172,48 @@ fn analysis_diagnostic(diagnostic: &Diagnostic, source: &SourceFile) -> Analysis
                 span: source_span(source, label.range),
             })
             .collect(),
+        source_line: span
+            .filter(|_| options.capture_source_lines)
+            .and_then(|span| source.line_text(span.start.line))
+            .map(str::to_string),
+    }
+}
+
+/// Ids depend only on a node's kind and its source text with line endings and
+/// trailing whitespace normalized, so inserting or removing other nodes keeps
+/// them stable. Identical nodes are told apart by an occurrence counter in
+/// document order; editing one of them renumbers the later duplicates.
+pub(crate) fn node_ids(nodes: &[SyntaxNode], text: &str) -> Vec<String> {
+    let mut occurrences = HashMap::<(&str, String), usize>::new();
+    nodes
+        .iter()
+        .map(|node| {
+            let range = node.range();
+            let content = text[range.start..range.end]
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n");
+            let kind = node_kind(node);
+            let occurrence = occurrences.entry((kind, content.clone())).or_default();
+            *occurrence += 1;
+            derived_key(
+                "mmt-syntax-node-id-v1",
+                &[kind, &content, &occurrence.to_string()],
+            )
+        })
+        .collect()
+}
+
+fn node_kind(node: &SyntaxNode) -> &'static str {
+    match node {
+        SyntaxNode::Statement(_) => "statement",
+        SyntaxNode::DirectiveLine(_) => "directive_line",
+        SyntaxNode::DirectiveBlock(_) => "directive_block",
+        SyntaxNode::Reply(_) => "reply",
+        SyntaxNode::Bond(_) => "bond",
+        SyntaxNode::Blank(_) => "blank",
+        SyntaxNode::Error(_) => "error",
     }
 }
 
@@ -123,6 +245,112 @@ mod tests {
         assert_eq!(value["diagnostics"][0]["span"]["start"]["column"], 1);
     }
 
+    #[test]
+    fn plain_body_parts_are_omitted_only_on_request() {
+        let source = "- 旁白\n> 柚子: 你好 [:happy:]\n@reply: A | B";
+        let full = analyze_text_json(source).unwrap();
+        let compact = analyze_text_json_with_options(
+            source,
+            &AnalysisOptions::default().with_omit_plain_body_parts(true),
+        )
+        .unwrap();
+        let nodes = |json: &str| {
+            serde_json::from_str::<serde_json::Value>(json).unwrap()["ast"]["nodes"].clone()
+        };
+
+        assert_eq!(nodes(&full)[0]["data"]["body"]["parts"][0]["kind"], "text");
+        assert!(nodes(&compact)[0]["data"]["body"].get("parts").is_none());
+        assert_eq!(nodes(&compact)[0]["data"]["body"]["source"], "旁白");
+        assert_eq!(
+            nodes(&compact)[1]["data"]["body"]["parts"][1]["kind"],
+            "inline_macro"
+        );
+        assert!(
+            nodes(&compact)[2]["data"]["items"][0]
+                .get("parts")
+                .is_none()
+        );
+        assert!(compact.len() < full.len());
+    }
+
+    #[test]
+    fn source_lines_are_captured_only_on_request() {
+        let source = "- 你好\r\n@end\n";
+        let plain: serde_json::Value =
+            serde_json::from_str(&analyze_text_json(source).unwrap()).unwrap();
+        let captured: serde_json::Value = serde_json::from_str(
+            &analyze_text_json_with_options(
+                source,
+                &AnalysisOptions::default().with_capture_source_lines(true),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert!(plain["diagnostics"][0].get("source_line").is_none());
+        assert_eq!(captured["diagnostics"][0]["source_line"], "@end");
+    }
+
+    #[test]
+    fn node_ids_survive_unrelated_insertions() {
+        let options = AnalysisOptions::default().with_emit_node_ids(true);
+        let ids = |source: &str| -> Vec<String> {
+            let json = analyze_text_json_with_options(source, &options).unwrap();
+            serde_json::from_str::<serde_json::Value>(&json).unwrap()["node_ids"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|id| id.as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let before = ids("> 柚子: 你好\n- 旁白\n- 旁白");
+        let after = ids("- 新的一行\n> 柚子: 你好  \r\n- 旁白\n- 旁白");
+
+        assert_eq!(before.len(), 3);
+        assert_ne!(before[1], before[2]);
+        assert_eq!(before, after[1..]);
+        assert!(!analyze_text_json("- 旁白").unwrap().contains("node_ids"));
+    }
+
+    #[test]
+    fn wasm_reports_errors_alongside_the_recovered_ast() {
+        let report: serde_json::Value =
+            serde_json::from_str(&analyze_text_wasm("- 你好\n@end\n- 再见")).unwrap();
+
+        assert_eq!(report["ast"]["nodes"].as_array().unwrap().len(), 3);
+        assert_eq!(report["diagnostics"].as_array().unwrap().len(), 1);
+        assert_eq!(report["diagnostics"][0]["severity"], "error");
+    }
+
+    #[test]
+    fn wasm_options_entry_reads_analysis_options_json() {
+        let source = "- 你好\n@end";
+        let options = AnalysisOptions::default()
+            .with_capture_source_lines(true)
+            .with_emit_node_ids(true)
+            .with_omit_plain_body_parts(true);
+
+        assert_eq!(
+            analyze_text_wasm_with_options(
+                source,
+                r#"{"capture_source_lines": true, "emit_node_ids": true, "omit_plain_body_parts": true}"#
+            ),
+            analyze_text_json_with_options(source, &options).unwrap()
+        );
+        assert_eq!(
+            analyze_text_wasm_with_options(source, "{}"),
+            analyze_text_wasm(source)
+        );
+        let rejected: serde_json::Value = serde_json::from_str(&analyze_text_wasm_with_options(
+            source,
+            r#"{"emit_node_id": true}"#,
+        ))
+        .unwrap();
+        assert!(rejected["ast"].is_null());
+        assert_eq!(rejected["diagnostics"][0]["severity"], "error");
+    }
+
     #[test]
     fn wasm_string_entry_matches_native_json_entry() {
         let source = "- 你好";
@@ -8,8 +8,9 @@ use std::process::ExitCode;
 
 use mmt_rs::pack::{PackManifest, PackRegistry};
 use mmt_rs::{
-    DocumentOverrides, EmitOptions, HostTimestamp, ProjectMaterializer, ProjectMaterializerOptions,
-    SourceSpan, compile_text_strict, export_template_library,
+    ActorEvent, DocumentOverrides, EmitOptions, HostTimestamp, LintConfig, LintLevel,
+    ProjectConfig, ProjectMaterializer, ProjectMaterializerOptions, SourceSpan, actor_timeline,
+    compile_text_strict, export_template_library, lint_codes, speaker_report,
 };
 use serde::Serialize;
 use time::{OffsetDateTime, format_description::well_known::Rfc3339};
@@ -32,6 +33,9 @@ struct Options {
     cache_dir: PathBuf,
     avifdec_bin: PathBuf,
     decoder_profile: String,
+    lints: LintConfig,
+    stats: bool,
+    trace_actor: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -39,6 +43,35 @@ struct CliReport {
     success: bool,
     output_dir: Option<String>,
     diagnostics: Vec<CliDiagnostic>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    speakers: Option<Vec<CliSpeaker>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<Vec<CliActorEvent>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum CliActorEvent {
+    Revision {
+        line: usize,
+        revision: u32,
+        display_name: String,
+        avatar: Option<String>,
+        nickname: Option<String>,
+    },
+    Speaks {
+        line: usize,
+        revision: u32,
+    },
+}
+
+#[derive(Serialize)]
+struct CliSpeaker {
+    name: String,
+    messages: usize,
+    characters: usize,
+    longest_run: usize,
+    share: f64,
 }
 
 #[derive(Serialize)]
@@ -46,6 +79,8 @@ struct CliDiagnostic {
     phase: String,
     severity: String,
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
     span: Option<SourceSpan>,
 }
 
@@ -90,16 +125,16 @@ fn run(args: Vec<OsString>) -> Result<CliReport, CliReport> {
         decoder_profile: options.decoder_profile,
     })
     .map_err(|error| host_error(error.message))?;
-    let emit_options = EmitOptions {
-        template_import: template_import.to_string(),
-        document_overrides: DocumentOverrides {
+    let emit_options = EmitOptions::default()
+        .with_template_import(template_import)
+        .with_document_overrides(DocumentOverrides {
             title: options.title,
             author: options.author,
             show_header: options.show_header,
             compiled_at: options.compiled_at,
-        },
-        timestamp: Some(timestamp),
-    };
+        })
+        .with_timestamp(Some(timestamp))
+        .with_lints(options.lints);
 
     match compile_text_strict(&source, &registry, &mut materializer, &emit_options) {
         Ok(compilation) => {
@@ -124,16 +159,36 @@ fn run(args: Vec<OsString>) -> Result<CliReport, CliReport> {
             .map_err(|error| host_error(format!("cannot serialize source map: {error}")))?;
             fs::write(options.output_dir.join("source-map.json"), source_map)
                 .map_err(|error| host_error(format!("cannot write source-map.json: {error}")))?;
+            let speakers = options.stats.then(|| {
+                speaker_report(&compilation.document, &compilation.actors)
+                    .into_iter()
+                    .map(|stats| CliSpeaker {
+                        name: stats.name,
+                        messages: stats.messages,
+                        characters: stats.characters,
+                        longest_run: stats.longest_run,
+                        share: stats.share,
+                    })
+                    .collect()
+            });
+            let trace = match &options.trace_actor {
+                Some(name) => Some(actor_trace(&source, &compilation.actors, name)?),
+                None => None,
+            };
             Ok(CliReport {
                 success: true,
                 output_dir: Some(options.output_dir.display().to_string()),
                 diagnostics: diagnostics(&source, &compilation.diagnostics),
+                speakers,
+                trace,
             })
         }
         Err(failure) => Err(CliReport {
             success: false,
             output_dir: None,
             diagnostics: diagnostics(&source, &failure.diagnostics),
+            speakers: None,
+            trace: None,
         }),
     }
 }
@@ -141,18 +196,25 @@ fn run(args: Vec<OsString>) -> Result<CliReport, CliReport> {
 fn parse_args(args: Vec<OsString>) -> Result<Options, String> {
     let mut input = None;
     let mut output_dir = None;
+    let mut config_path = None;
+    let mut no_config = false;
     let mut manifests = Vec::new();
-    let mut template_dir = PathBuf::from("typst_sandbox/mmt_render");
-    let mut workspace_root = env::current_dir().map_err(|error| error.to_string())?;
+    let mut template_dir = None;
+    let mut workspace_root = None;
     let mut title = None;
     let mut show_header = None;
     let mut use_local_template_package = false;
     let mut author = None;
     let mut compiled_at = None;
     let mut clock = None;
-    let mut cache_dir = PathBuf::from(".cache/mmt-rs/materialized");
+    let mut cache_dir = None;
     let mut avifdec_bin = PathBuf::from("avifdec");
     let mut decoder_profile = "avifdec-dav1d-png-v1".to_string();
+    let mut lint_rules = Vec::new();
+    let mut max_speaker_share = None;
+    let mut max_continuation_lines = None;
+    let mut stats = false;
+    let mut trace_actor = None;
     let mut args = args.into_iter();
     while let Some(arg) = args.next() {
         let arg = arg
@@ -170,10 +232,14 @@ fn parse_args(args: Vec<OsString>) -> Result<Options, String> {
         match arg.as_str() {
             "--input" => input = Some(PathBuf::from(value(&mut args, "--input")?)),
             "--output-dir" => output_dir = Some(PathBuf::from(value(&mut args, "--output-dir")?)),
+            "--config" => config_path = Some(PathBuf::from(value(&mut args, "--config")?)),
+            "--no-config" => no_config = true,
             "--manifest" => manifests.push(PathBuf::from(value(&mut args, "--manifest")?)),
-            "--template-dir" => template_dir = PathBuf::from(value(&mut args, "--template-dir")?),
+            "--template-dir" => {
+                template_dir = Some(PathBuf::from(value(&mut args, "--template-dir")?))
+            }
             "--workspace-root" => {
-                workspace_root = PathBuf::from(value(&mut args, "--workspace-root")?)
+                workspace_root = Some(PathBuf::from(value(&mut args, "--workspace-root")?))
             }
             "--title" => title = Some(value(&mut args, "--title")?),
             "--use-local-template-package" => use_local_template_package = true,
@@ -182,28 +248,111 @@ fn parse_args(args: Vec<OsString>) -> Result<Options, String> {
             "--author" => author = Some(value(&mut args, "--author")?),
             "--compiled-at" => compiled_at = Some(value(&mut args, "--compiled-at")?),
             "--clock" => clock = Some(value(&mut args, "--clock")?),
-            "--cache-dir" => cache_dir = PathBuf::from(value(&mut args, "--cache-dir")?),
+            "--cache-dir" => cache_dir = Some(PathBuf::from(value(&mut args, "--cache-dir")?)),
             "--avifdec-bin" => avifdec_bin = PathBuf::from(value(&mut args, "--avifdec-bin")?),
             "--decoder-profile" => decoder_profile = value(&mut args, "--decoder-profile")?,
+            "--allow" | "--warn" | "--deny" => {
+                let code = value(&mut args, &arg)?;
+                if !lint_codes().any(|known| known == code) {
+                    return Err(format!(
+                        "unknown lint '{code}'; expected one of: {}",
+                        lint_codes().collect::<Vec<_>>().join(", ")
+                    ));
+                }
+                let level = match arg.as_str() {
+                    "--allow" => LintLevel::Allow,
+                    "--warn" => LintLevel::Warn,
+                    _ => LintLevel::Deny,
+                };
+                lint_rules.push((code, level));
+            }
+            "--max-speaker-share" => {
+                let percent = value(&mut args, "--max-speaker-share")?;
+                let percent = percent
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|percent| *percent <= 100)
+                    .ok_or_else(|| {
+                        format!("--max-speaker-share must be a percentage from 0 to 100, got '{percent}'")
+                    })?;
+                max_speaker_share = Some(percent);
+            }
+            "--max-continuation-lines" => {
+                let lines = value(&mut args, "--max-continuation-lines")?;
+                let lines = lines.parse::<usize>().map_err(|_| {
+                    format!("--max-continuation-lines must be a line count, got '{lines}'")
+                })?;
+                max_continuation_lines = Some(lines);
+            }
+            "--stats" => stats = true,
+            "--trace-actor" => trace_actor = Some(value(&mut args, "--trace-actor")?),
             "--help" | "-h" => return Err(usage()),
             _ => return Err(format!("unknown argument '{arg}'\n{}", usage())),
         }
     }
+    let output_dir = output_dir.ok_or_else(usage)?;
+    let current_dir = env::current_dir().map_err(|error| error.to_string())?;
+    // Explicit arguments win over the nearest mmt.toml, which wins over the
+    // built-in defaults; command-line manifests are added to configured ones.
+    let config = match (config_path, no_config) {
+        (Some(_), true) => {
+            return Err("only one of --config or --no-config may be supplied".to_string());
+        }
+        (Some(path), false) => Some(ProjectConfig::from_file(&path)),
+        (None, false) => {
+            let start = match input.as_deref() {
+                Some(path) if path != Path::new("-") => path
+                    .parent()
+                    .map(|parent| current_dir.join(parent))
+                    .unwrap_or_else(|| current_dir.clone()),
+                _ => current_dir.clone(),
+            };
+            ProjectConfig::discover(&start).transpose()
+        }
+        (None, true) => None,
+    }
+    .transpose()
+    .map_err(|error| error.to_string())?
+    .unwrap_or_default();
+    let mut lints = config.lints;
+    for (code, level) in lint_rules {
+        lints = lints.with_rule(code, level);
+    }
+    // A threshold flag is a request to run its rule.
+    if let Some(percent) = max_speaker_share {
+        lints = lints
+            .with_max_speaker_share(percent)
+            .with_rule_enabled("speaker-balance");
+    }
+    if let Some(lines) = max_continuation_lines {
+        lints = lints
+            .with_max_continuation_lines(lines)
+            .with_rule_enabled("continuation-lines");
+    }
     Ok(Options {
         input,
-        output_dir: output_dir.ok_or_else(usage)?,
-        manifests,
-        template_dir,
-        workspace_root,
-        title,
-        show_header,
-        author,
+        output_dir,
+        manifests: config.manifests.into_iter().chain(manifests).collect(),
+        template_dir: template_dir
+            .or(config.template_dir)
+            .unwrap_or_else(|| PathBuf::from("typst_sandbox/mmt_render")),
+        workspace_root: workspace_root
+            .or(config.workspace_root)
+            .unwrap_or(current_dir),
+        title: title.or(config.title),
+        show_header: show_header.or(config.show_header),
+        author: author.or(config.author),
         compiled_at,
         clock,
         use_local_template_package,
-        cache_dir,
+        cache_dir: cache_dir
+            .or(config.cache_dir)
+            .unwrap_or_else(|| PathBuf::from(".cache/mmt-rs/materialized")),
         avifdec_bin,
         decoder_profile,
+        lints,
+        stats,
+        trace_actor,
     })
 }
 
@@ -248,7 +397,7 @@ fn current_clock() -> Result<HostTimestamp, String> {
 }
 
 fn usage() -> String {
-    "usage: mmt-compile [--input FILE] --output-dir DIR [--manifest FILE ...] [--template-dir DIR] [--use-local-template-package] [--workspace-root DIR] [--cache-dir DIR] [--avifdec-bin FILE] [--decoder-profile ID] [--title TEXT] [--author TEXT] [--show-header | --no-header] [--compiled-at TEXT] [--clock RFC3339]".to_string()
+    "usage: mmt-compile [--input FILE] --output-dir DIR [--config FILE | --no-config] [--manifest FILE ...] [--template-dir DIR] [--use-local-template-package] [--workspace-root DIR] [--cache-dir DIR] [--avifdec-bin FILE] [--decoder-profile ID] [--title TEXT] [--author TEXT] [--show-header | --no-header] [--compiled-at TEXT] [--clock RFC3339] [--allow LINT | --warn LINT | --deny LINT ...] [--max-speaker-share PERCENT] [--max-continuation-lines N] [--stats] [--trace-actor NAME]".to_string()
 }
 
 fn read_source(path: Option<&Path>) -> Result<String, String> {
@@ -290,6 +439,42 @@ fn load_registry(paths: &[PathBuf]) -> Result<(PackRegistry, HashMap<String, Pat
     Ok((registry, roots))
 }
 
+/// Revisions and statements of the actor declared or referenced as `name`,
+/// with 1-based source lines.
+fn actor_trace(
+    source: &str,
+    actors: &mmt_rs::ActorLowering,
+    name: &str,
+) -> Result<Vec<CliActorEvent>, CliReport> {
+    let actor = actors
+        .actors
+        .iter()
+        .find(|actor| actor.names.iter().any(|known| known == name))
+        .ok_or_else(|| host_error(format!("--trace-actor: no actor named '{name}'")))?;
+    let source_file = mmt_rs::source::SourceFile::anonymous(source);
+    let line = |event: &ActorEvent| {
+        source_file
+            .line_column(event.range().start)
+            .map_or(0, |position| position.line)
+    };
+    Ok(actor_timeline(actors, actor.id)
+        .into_iter()
+        .map(|event| match &event {
+            ActorEvent::Revision { number, state, .. } => CliActorEvent::Revision {
+                line: line(&event),
+                revision: *number,
+                display_name: state.display_name.clone(),
+                avatar: state.avatar.clone(),
+                nickname: state.nickname.clone(),
+            },
+            ActorEvent::Speaks { revision, .. } => CliActorEvent::Speaks {
+                line: line(&event),
+                revision: *revision,
+            },
+        })
+        .collect())
+}
+
 fn diagnostics(source: &str, items: &[mmt_rs::diag::Diagnostic]) -> Vec<CliDiagnostic> {
     let source_file = mmt_rs::source::SourceFile::anonymous(source);
     items
@@ -298,6 +483,7 @@ fn diagnostics(source: &str, items: &[mmt_rs::diag::Diagnostic]) -> Vec<CliDiagn
             phase: format!("{:?}", diagnostic.phase).to_lowercase(),
             severity: format!("{:?}", diagnostic.severity).to_lowercase(),
             message: diagnostic.message.clone(),
+            code: diagnostic.code.map(str::to_string),
             span: diagnostic.range.and_then(|range| {
                 let start = source_file.line_column(range.start)?;
                 let end = source_file.line_column(range.end)?;
@@ -315,7 +501,10 @@ fn host_error(message: impl Into<String>) -> CliReport {
             phase: "host".to_string(),
             severity: "error".to_string(),
             message: message.into(),
+            code: None,
             span: None,
         }],
+        speakers: None,
+        trace: None,
     }
 }
@@ -0,0 +1,185 @@
+//! Project-wide compile defaults read from an `mmt.toml` file.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::emit::EmitOptions;
+use crate::lint::{LintConfig, LintLevel, lint_codes};
+use crate::semantic::DocumentOverrides;
+
+pub const PROJECT_CONFIG_FILE: &str = "mmt.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectConfigError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for ProjectConfigError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "invalid project config '{}': {}",
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ProjectConfigError {}
+
+/// Defaults shared by every script below the directory holding `mmt.toml`.
+/// Paths are resolved against that directory. Hosts apply explicit options
+/// on top of these values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectConfig {
+    pub root: PathBuf,
+    pub manifests: Vec<PathBuf>,
+    pub workspace_root: Option<PathBuf>,
+    pub template_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub show_header: Option<bool>,
+    pub lints: LintConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct RawProjectConfig {
+    #[serde(default)]
+    manifests: Vec<PathBuf>,
+    workspace_root: Option<PathBuf>,
+    template_dir: Option<PathBuf>,
+    cache_dir: Option<PathBuf>,
+    title: Option<String>,
+    author: Option<String>,
+    show_header: Option<bool>,
+    #[serde(default)]
+    lints: BTreeMap<String, LintLevel>,
+    max_speaker_share: Option<u8>,
+    max_continuation_lines: Option<usize>,
+}
+
+impl ProjectConfig {
+    /// Finds the nearest `mmt.toml` in `start` or one of its ancestors.
+    pub fn discover(start: &Path) -> Result<Option<Self>, ProjectConfigError> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+            .find(|path| path.is_file())
+            .map(|path| Self::from_file(&path))
+            .transpose()
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ProjectConfigError> {
+        let source = fs::read_to_string(path).map_err(|error| ProjectConfigError {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        })?;
+        let root = path.parent().unwrap_or_else(|| Path::new("."));
+        Self::from_toml(&source, root).map_err(|message| ProjectConfigError {
+            path: path.to_path_buf(),
+            message,
+        })
+    }
+
+    pub fn from_toml(source: &str, root: &Path) -> Result<Self, String> {
+        let raw: RawProjectConfig = toml::from_str(source).map_err(|error| error.to_string())?;
+        let mut lints = LintConfig::default();
+        for (code, level) in raw.lints {
+            if !lint_codes().any(|known| known == code) {
+                return Err(format!(
+                    "unknown lint '{code}' in [lints]; expected one of: {}",
+                    lint_codes().collect::<Vec<_>>().join(", ")
+                ));
+            }
+            lints = lints.with_rule(code, level);
+        }
+        if let Some(percent) = raw.max_speaker_share {
+            if percent > 100 {
+                return Err(format!(
+                    "max-speaker-share must be a percentage from 0 to 100, got {percent}"
+                ));
+            }
+            lints = lints
+                .with_max_speaker_share(percent)
+                .with_rule_enabled("speaker-balance");
+        }
+        if let Some(lines) = raw.max_continuation_lines {
+            lints = lints
+                .with_max_continuation_lines(lines)
+                .with_rule_enabled("continuation-lines");
+        }
+        let resolve = |path: PathBuf| root.join(path);
+        Ok(Self {
+            root: root.to_path_buf(),
+            manifests: raw.manifests.into_iter().map(resolve).collect(),
+            workspace_root: raw.workspace_root.map(resolve),
+            template_dir: raw.template_dir.map(resolve),
+            cache_dir: raw.cache_dir.map(resolve),
+            title: raw.title,
+            author: raw.author,
+            show_header: raw.show_header,
+            lints,
+        })
+    }
+
+    /// Emit options seeded with the configured document overrides and lints.
+    pub fn emit_options(&self) -> EmitOptions {
+        EmitOptions::default()
+            .with_document_overrides(DocumentOverrides {
+                title: self.title.clone(),
+                author: self.author.clone(),
+                show_header: self.show_header,
+                compiled_at: None,
+            })
+            .with_lints(self.lints.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_paths_against_the_config_directory() {
+        let config = ProjectConfig::from_toml(
+            "manifests = [\"packs/ba/manifest.json\"]\n\
+             template-dir = \"template\"\n\
+             title = \"社团日常\"\n\
+             max-speaker-share = 60\n\
+             [lints]\n\
+             side-flip = \"deny\"",
+            Path::new("/work"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.manifests,
+            vec![PathBuf::from("/work/packs/ba/manifest.json")]
+        );
+        assert_eq!(config.template_dir, Some(PathBuf::from("/work/template")));
+        assert_eq!(config.lints.level("side-flip"), LintLevel::Deny);
+        assert_eq!(config.lints.max_speaker_share, 60);
+        assert_eq!(config.lints.level("speaker-balance"), LintLevel::Warn);
+        assert_eq!(
+            config.emit_options().document_overrides.title.as_deref(),
+            Some("社团日常")
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_lints_and_levels() {
+        let error = |source| ProjectConfig::from_toml(source, Path::new(".")).unwrap_err();
+
+        assert!(error("tittle = \"x\"").contains("unknown field `tittle`"));
+        assert!(error("[lints]\nside-flop = \"warn\"").starts_with("unknown lint 'side-flop'"));
+        assert!(error("[lints]\nside-flip = \"loud\"").contains("unknown variant `loud`"));
+        assert!(error("max-speaker-share = 120").contains("got 120"));
+    }
+}
@@ -32,6 +32,9 @@ pub struct Diagnostic {
     pub message: String,
     pub range: Option<TextRange>,
     pub labels: Vec<DiagnosticLabel>,
+    /// Rule code for diagnostics produced by a configurable lint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
 }
 
 impl Diagnostic {
@@ -47,6 +50,7 @@ impl Diagnostic {
             message: message.into(),
             range,
             labels: Vec::new(),
+            code: None,
         }
     }
 
@@ -5,6 +5,7 @@ use std::collections::{HashMap, HashSet};
 use serde::Serialize;
 
 use crate::diag::{Diagnostic, DiagnosticPhase, Severity};
+use crate::lint::LintConfig;
 use crate::semantic::{
     ActorId, ActorLowering, BodyModeResolution, BuiltinSpeakerId, DocumentConfig,
     DocumentOverrides, DocumentPresentation, HostTimestamp, ResolvedBodyMode,
@@ -126,11 +127,17 @@ pub struct BuiltinPresentation {
     pub reserve_avatar_space: bool,
 }
 
+/// Host-side emission settings.
+///
+/// Construct from [`EmitOptions::default`] and the `with_*` setters so new
+/// settings can be added without breaking callers outside this crate.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct EmitOptions {
     pub template_import: String,
     pub document_overrides: DocumentOverrides,
     pub timestamp: Option<HostTimestamp>,
+    pub lints: LintConfig,
 }
 
 impl Default for EmitOptions {
@@ -139,10 +146,33 @@ impl Default for EmitOptions {
             template_import: "typst_sandbox/mmt_render/lib.typ".to_string(),
             document_overrides: DocumentOverrides::default(),
             timestamp: None,
+            lints: LintConfig::default(),
         }
     }
 }
 
+impl EmitOptions {
+    pub fn with_template_import(mut self, template_import: impl Into<String>) -> Self {
+        self.template_import = template_import.into();
+        self
+    }
+
+    pub fn with_document_overrides(mut self, document_overrides: DocumentOverrides) -> Self {
+        self.document_overrides = document_overrides;
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: Option<HostTimestamp>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_lints(mut self, lints: LintConfig) -> Self {
+        self.lints = lints;
+        self
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct MaterializedContent {
     pub actor_avatars: HashMap<(ActorId, u32), String>,
@@ -207,6 +237,7 @@ struct ChatGroupKey {
 struct SpeakerView {
     identity: SpeakerIdentity,
     display_name: Option<String>,
+    nickname: Option<String>,
     avatar_path: Option<String>,
     reserve_avatar_space: bool,
 }
@@ -389,6 +420,16 @@ impl<'a> TypstEmitter<'a> {
                 Some(parent),
             );
         }
+        if let Some(nickname) = view.nickname {
+            self.builder.push_generated(
+                &format!(
+                    "  nickname: [#text(\"{}\")],\n",
+                    escape_typst_string(&nickname)
+                ),
+                GeneratedKind::StatementCallWrapper,
+                Some(parent),
+            );
+        }
         if let Some(path) = view.avatar_path {
             self.builder.push_generated(
                 &format!(
@@ -434,6 +475,7 @@ impl<'a> TypstEmitter<'a> {
                 Some(SpeakerView {
                     identity: speaker.speaker.clone(),
                     display_name: Some(revision.state.display_name.clone()),
+                    nickname: revision.state.nickname.clone(),
                     avatar_path,
                     reserve_avatar_space: true,
                 })
@@ -443,6 +485,7 @@ impl<'a> TypstEmitter<'a> {
                 Some(SpeakerView {
                     identity: speaker.speaker.clone(),
                     display_name: presentation.and_then(|item| item.name.clone()),
+                    nickname: None,
                     avatar_path: presentation.and_then(|item| item.avatar_path.clone()),
                     reserve_avatar_space: presentation
                         .is_some_and(|item| item.reserve_avatar_space),
@@ -462,6 +505,13 @@ impl<'a> TypstEmitter<'a> {
             Some(parent),
         );
         self.emit_patch(reply.patch.as_ref(), parent);
+        if let Some(selected) = reply.selected {
+            self.builder.push_generated(
+                &format!("selected: {selected},"),
+                GeneratedKind::StatementCallWrapper,
+                Some(parent),
+            );
+        }
         self.builder
             .push_generated(")", GeneratedKind::StatementCallWrapper, Some(parent));
         for item in &reply.items {
@@ -779,12 +829,7 @@ mod tests {
 
     fn lower(source: &str) -> (SyntaxDocument, BodyModeResolution, ActorLowering) {
         let document = parse_text(source);
-        let catalog = StaticPresetCatalog::new(vec![CharacterPreset {
-            id: "ba::柚子".to_string(),
-            names: vec!["柚子".to_string()],
-            display_name: None,
-            avatar: None,
-        }]);
+        let catalog = StaticPresetCatalog::new(vec![CharacterPreset::new("ba::柚子", ["柚子"])]);
         let modes = resolve_body_modes(&document);
         let actors = lower_actors(&document, &catalog);
         (document, modes, actors)
@@ -824,10 +869,7 @@ mod tests {
             &modes,
             &actors,
             &MaterializedContent::default(),
-            &EmitOptions {
-                timestamp: Some(HostTimestamp::new(0, -300).unwrap()),
-                ..EmitOptions::default()
-            },
+            &EmitOptions::default().with_timestamp(Some(HostTimestamp::new(0, -300).unwrap())),
         );
         assert!(emitted.diagnostics.is_empty());
         assert!(emitted.source.contains("show-header: false"));
@@ -868,6 +910,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn passes_actor_nicknames_beside_display_names() {
+        let emitted = emit(
+            "@actor 柚子\n\
+             preset: ba::柚子\n\
+             nickname: 柚\n\
+             @end\n\
+             > 柚子: one",
+        );
+
+        assert!(emitted.diagnostics.is_empty());
+        assert!(emitted.source.contains("  name: [#text(\"柚子\")],\n"));
+        assert!(emitted.source.contains("  nickname: [#text(\"柚\")],\n"));
+    }
+
+    #[test]
+    fn comments_render_nothing_and_keep_messages_grouped() {
+        let source = "> 柚子: one\n\
+                      @comment\n\
+                      > 柚子: 删掉的台词\n\
+                      @end\n\
+                      @comment: 也不渲染\n\
+                      > two";
+        let emitted = emit(source);
+
+        assert!(lower_document(&parse_text(source)).diagnostics.is_empty());
+        assert!(emitted.diagnostics.is_empty());
+        assert!(emitted.source.contains("auto-continued: true"));
+        assert!(!emitted.source.contains("删掉的台词"));
+        assert!(!emitted.source.contains("也不渲染"));
+    }
+
+    #[test]
+    fn right_to_left_text_and_direction_marks_pass_through_unchanged() {
+        let body = "مرحبا يا \u{2067}Yuzu\u{2069}، كيف الحال?\u{200f}";
+        let emitted = emit(&format!("> 柚子: {body}\n- \u{200e}(ملاحظة)"));
+
+        assert!(emitted.diagnostics.is_empty());
+        assert!(emitted.source.contains(&format!("#text(\"{body}\")")));
+        assert!(emitted.source.contains("#text(\"\u{200e}(ملاحظة)\")"));
+    }
+
+    #[test]
+    fn typst_significant_characters_in_text_bodies_stay_literal() {
+        let emitted = emit(
+            "> 柚子: #let x = 1 写法 @桃井 看 *这里* 和 _那里_ $a$ <tag>\n\
+             - 路径 C:\\mmt 与 \"引号\"",
+        );
+
+        assert!(emitted.diagnostics.is_empty());
+        assert!(
+            emitted
+                .source
+                .contains("#text(\"#let x = 1 写法 @桃井 看 *这里* 和 _那里_ $a$ <tag>\")")
+        );
+        assert!(
+            emitted
+                .source
+                .contains("#text(\"路径 C:\\\\mmt 与 \\\"引号\\\"\")")
+        );
+        assert!(
+            check_typst_source(&emitted.source, TextRange::new(0, emitted.source.len())).is_empty()
+        );
+    }
+
     #[test]
     fn decodes_escaped_reply_separator_in_emitted_text() {
         let emitted = emit(r#"@reply: 是 | 不知道\|算了"#);
@@ -880,6 +987,20 @@ mod tests {
         assert!(!emitted.source.contains(r#"不知道\|算了"#));
     }
 
+    #[test]
+    fn selected_reply_item_is_passed_as_an_index() {
+        let emitted = emit("@reply(label: [选择]): A | * B");
+        assert!(emitted.diagnostics.is_empty());
+        assert!(
+            emitted
+                .source
+                .contains("#mmt.reply(label: [选择],\nselected: 1,)[#text(\"A\")][#text(\"B\")]")
+        );
+        assert!(
+            check_typst_source(&emitted.source, TextRange::new(0, emitted.source.len())).is_empty()
+        );
+    }
+
     #[test]
     fn empty_patches_emit_no_argument_or_separator() {
         let emitted = emit(
@@ -55,7 +55,19 @@ pub struct InlineMacroDiagnostic {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum InlineMacroParseError {
-    MissingClose { range: TextRange },
+    MissingClose {
+        range: TextRange,
+    },
+    /// `[:` opens an ordinary bracket pair, as in a `a[:n]` slice or a
+    /// `[:3」∠]` kaomoji, or the candidate has unbalanced brackets before
+    /// `:]`. The text is literal and needs no diagnostic.
+    NotAMacro,
+}
+
+enum MacroClose {
+    Found(usize),
+    Literal,
+    Missing,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -268,9 +280,15 @@ pub fn parse_inline_macro_at_checked(
         });
     }
 
-    let close = find_macro_close(text).ok_or(InlineMacroParseError::MissingClose {
-        range: TextRange::new(absolute_start, absolute_start + text.len()),
-    })?;
+    let close = match find_macro_close(text) {
+        MacroClose::Found(close) => close,
+        MacroClose::Literal => return Err(InlineMacroParseError::NotAMacro),
+        MacroClose::Missing => {
+            return Err(InlineMacroParseError::MissingClose {
+                range: TextRange::new(absolute_start, absolute_start + text.len()),
+            });
+        }
+    };
     let args_text = &text[2..close];
     let args_range = TextRange::new(absolute_start + 2, absolute_start + close);
     let mut range_end = absolute_start + close + 2;
@@ -386,7 +404,7 @@ fn parse_namespaced_value(raw: &str) -> MacroValueSyntax {
     MacroValueSyntax::Bare(raw.to_string())
 }
 
-fn unquote(raw: &str, quote: char) -> Option<String> {
+pub(crate) fn unquote(raw: &str, quote: char) -> Option<String> {
     if !raw.starts_with(quote) || !raw.ends_with(quote) || raw.len() < 2 {
         return None;
     }
@@ -409,9 +427,13 @@ fn unquote(raw: &str, quote: char) -> Option<String> {
     Some(value)
 }
 
-fn find_macro_close(text: &str) -> Option<usize> {
+/// Finds the `:]` closing a `[:` candidate. Brackets opened inside the
+/// candidate must close in order before it; a stray closer, including the
+/// `]` of a plain `[:...]` group, makes the whole candidate literal text.
+fn find_macro_close(text: &str) -> MacroClose {
     let mut quote = None;
     let mut escaped = false;
+    let mut closers = Vec::new();
     let mut iter = text.char_indices().skip(2);
     while let Some((offset, ch)) = iter.next() {
         if escaped {
@@ -432,11 +454,18 @@ fn find_macro_close(text: &str) -> Option<usize> {
             quote = Some(ch);
             continue;
         }
-        if ch == ':' && matches!(iter.clone().next(), Some((_, ']'))) {
-            return Some(offset);
+        match ch {
+            ':' if closers.is_empty() && matches!(iter.clone().next(), Some((_, ']'))) => {
+                return MacroClose::Found(offset);
+            }
+            '(' => closers.push(')'),
+            '[' => closers.push(']'),
+            '{' => closers.push('}'),
+            ')' | ']' | '}' if closers.pop() != Some(ch) => return MacroClose::Literal,
+            _ => {}
         }
     }
-    None
+    MacroClose::Missing
 }
 
 fn split_top_level_commas(text: &str) -> Vec<(usize, usize)> {
@@ -584,6 +613,24 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn plain_bracket_groups_and_unbalanced_candidates_are_not_macros() {
+        for text in ["[:n] + 1", "[:3」∠]", "[:a(b] c:]", "[:x) :]"] {
+            assert_eq!(
+                parse_inline_macro_at_checked(text, 0),
+                Err(InlineMacroParseError::NotAMacro),
+                "{text:?}"
+            );
+        }
+
+        let parsed = parse_inline_macro_at_checked("[:[1], \"(\":]", 0).unwrap();
+        assert_eq!(parsed.syntax.args.len(), 2);
+        assert!(matches!(
+            parse_inline_macro_at_checked("[:foo", 0),
+            Err(InlineMacroParseError::MissingClose { .. })
+        ));
+    }
+
     #[test]
     fn checked_parser_reports_unclosed_render_patch() {
         let parsed =
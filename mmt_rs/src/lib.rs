@@ -1,8 +1,11 @@
 pub mod analysis;
+pub mod config;
 pub mod diag;
 pub mod emit;
 pub mod identity;
 pub mod inline;
+pub mod lint;
+pub mod localize;
 pub mod materialize;
 pub mod pack;
 pub mod parser;
@@ -10,17 +13,23 @@ pub mod pipeline;
 pub mod project;
 pub mod projected_edit;
 pub mod projection;
+pub mod rename;
 pub mod resolve;
 pub mod semantic;
+pub mod sides;
 pub mod source;
+pub mod split;
+pub mod stats;
 pub mod syntax;
 pub mod typst_check;
 
 
 pub use analysis::{
-    ANALYSIS_SCHEMA, AnalysisDiagnostic, AnalysisLabel, AnalysisReport, AstReport, SourceSpan,
-    analyze_text_json, analyze_text_wasm,
+    ANALYSIS_SCHEMA, AnalysisDiagnostic, AnalysisLabel, AnalysisOptions, AnalysisReport, AstReport,
+    SourceSpan, analyze_text_json, analyze_text_json_with_options, analyze_text_wasm,
+    analyze_text_wasm_with_options,
 };
+pub use config::{PROJECT_CONFIG_FILE, ProjectConfig, ProjectConfigError};
 pub use emit::{
     BuiltinPresentation, EmitOptions, EmittedTypst, MaterializedContent, SourceMapEntry, emit_typst,
 };
@@ -31,6 +40,10 @@ pub use identity::{
     canonical_relative_path, derived_key, logical_source_id, materialization_key,
     project_snapshot_key, projection_key, render_key, runtime_artifact_key, source_content_key,
 };
+pub use lint::{LintConfig, LintLevel, lint_codes, lint_document};
+pub use localize::{
+    ExtractedString, ExtractedStringKind, LocalizeError, apply_strings, extract_strings,
+};
 pub use materialize::{
     Materialization, MaterializeError, MaterializedImage, ResourceMaterializer,
     materialize_resources,
@@ -56,9 +69,11 @@ pub use projection::{
     diagnose_analyzed_with_pack, diagnose_text, diagnose_text_with_pack, project_analyzed,
     project_analyzed_with_pack, project_text, project_text_with_pack,
 };
+pub use rename::{RenameError, rename_actor};
 pub use resolve::{
-    PackStorageSource, ResolvedResource, ResolvedResourceKind, ResourceFailure, ResourceResolution,
-    ResourceTarget, resolve_actor_avatars, resolve_resources,
+    PackStorageSource, RequiredFile, RequiredFileSource, ResolvedResource, ResolvedResourceKind,
+    ResourceFailure, ResourceResolution, ResourceTarget, required_files, resolve_actor_avatars,
+    resolve_resources,
 };
 pub use semantic::{
     ActorId, ActorLowering, ActorLoweringOptions, ActorRevision, ActorState, AssetId,
@@ -71,6 +86,12 @@ pub use semantic::{
     lower_actors_with_options, lower_assets, lower_document, lower_resource_markers,
     resolve_body_modes, resolve_document_presentation,
 };
+pub use sides::fix_side_flips;
+pub use split::split_sentences;
+pub use stats::{
+    ActorEvent, NameResolution, SpeakerStats, SpeakerToken, actor_timeline, speaker_report,
+    speaker_tokens,
+};
 pub use typst_check::{
     TypstCheckConfig, TypstOverlayScan, check_typst_args, check_typst_source,
     scan_typst_overlay_macros,
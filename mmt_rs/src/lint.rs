@@ -0,0 +1,410 @@
+//! Optional style checks with per-rule levels.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Deserialize;
+
+use crate::diag::{Diagnostic, DiagnosticPhase, Severity};
+use crate::pipeline::AnalyzedDocument;
+use crate::semantic::actor::side_name;
+use crate::semantic::{ResolvedStatementSpeaker, ScriptActor, SpeakerIdentity};
+use crate::stats::speaker_report;
+use crate::syntax::{StatementSyntax, SyntaxDocument, SyntaxNode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// Levels for individual lint rules, keyed by rule code. Rules without an
+/// entry are allowed, so the default configuration reports nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintConfig {
+    pub rules: BTreeMap<String, LintLevel>,
+    /// Largest percentage of spoken characters one speaker may have before
+    /// `speaker-balance` reports it.
+    pub max_speaker_share: u8,
+    /// Continuation lines one statement may have before `continuation-lines`
+    /// reports it.
+    pub max_continuation_lines: usize,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+            max_speaker_share: 80,
+            max_continuation_lines: 0,
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn with_rule(mut self, code: impl Into<String>, level: LintLevel) -> Self {
+        self.rules.insert(code.into(), level);
+        self
+    }
+
+    /// Sets `code` to warn unless a level is already configured for it, for
+    /// thresholds that would otherwise configure a rule nobody runs.
+    pub fn with_rule_enabled(self, code: &str) -> Self {
+        if self.rules.contains_key(code) {
+            return self;
+        }
+        self.with_rule(code, LintLevel::Warn)
+    }
+
+    pub fn with_max_speaker_share(mut self, percent: u8) -> Self {
+        self.max_speaker_share = percent;
+        self
+    }
+
+    pub fn with_max_continuation_lines(mut self, lines: usize) -> Self {
+        self.max_continuation_lines = lines;
+        self
+    }
+
+    pub fn level(&self, code: &str) -> LintLevel {
+        self.rules.get(code).copied().unwrap_or(LintLevel::Allow)
+    }
+}
+
+type LintRule = fn(&AnalyzedDocument, &LintConfig) -> Vec<Diagnostic>;
+
+const RULES: &[(&str, LintRule)] = &[
+    ("side-flip", |analysis, _| {
+        side_flips(
+            &analysis.document,
+            &analysis.actors.actors,
+            &analysis.actors.speakers,
+        )
+    }),
+    ("duplicate-message", |analysis, _| {
+        duplicate_messages(analysis)
+    }),
+    ("speaker-balance", speaker_balance),
+    ("continuation-lines", continuation_lines),
+    ("builtin-fallback", |analysis, _| {
+        builtin_fallbacks(analysis)
+    }),
+];
+
+/// Codes of every registered lint rule.
+pub fn lint_codes() -> impl Iterator<Item = &'static str> {
+    RULES.iter().map(|(code, _)| *code)
+}
+
+pub fn lint_document(analysis: &AnalyzedDocument, config: &LintConfig) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (code, rule) in RULES {
+        let severity = match config.level(code) {
+            LintLevel::Allow => continue,
+            LintLevel::Warn => Severity::Warning,
+            LintLevel::Deny => Severity::Error,
+        };
+        diagnostics.extend(rule(analysis, config).into_iter().map(|mut diagnostic| {
+            diagnostic.severity = severity;
+            diagnostic.code = Some(code);
+            diagnostic
+        }));
+    }
+    diagnostics
+}
+
+/// Warns each time an actor starts speaking from the opposite side.
+pub(crate) fn side_flips(
+    document: &SyntaxDocument,
+    actors: &[ScriptActor],
+    speakers: &[ResolvedStatementSpeaker],
+) -> Vec<Diagnostic> {
+    let kinds = statements(document)
+        .map(|statement| (statement.range, statement.kind))
+        .collect::<HashMap<_, _>>();
+    let mut sides = HashMap::new();
+    let mut diagnostics = Vec::new();
+    for speaker in speakers {
+        let SpeakerIdentity::Actor(actor_id) = speaker.speaker else {
+            continue;
+        };
+        let Some(&kind) = kinds.get(&speaker.statement_range) else {
+            continue;
+        };
+        let Some(&(previous_kind, previous_range)) = sides.get(&actor_id) else {
+            sides.insert(actor_id, (kind, speaker.statement_range));
+            continue;
+        };
+        if previous_kind == kind {
+            continue;
+        }
+        sides.insert(actor_id, (kind, speaker.statement_range));
+        let name = &actors[actor_id.0 as usize].primary_name;
+        diagnostics.push(
+            Diagnostic::new(
+                Severity::Warning,
+                DiagnosticPhase::Semantic,
+                format!(
+                    "actor '{name}' switches to {} dialogue after speaking as {} dialogue",
+                    side_name(kind),
+                    side_name(previous_kind)
+                ),
+                Some(speaker.statement_range),
+            )
+            .with_label(previous_range, "previous side established here"),
+        );
+    }
+    diagnostics
+}
+
+/// Warns when a statement repeats the body of the statement right before it
+/// from the same speaker.
+fn duplicate_messages(analysis: &AnalyzedDocument) -> Vec<Diagnostic> {
+    let speakers = analysis
+        .actors
+        .speakers
+        .iter()
+        .map(|speaker| (speaker.statement_range, &speaker.speaker))
+        .collect::<HashMap<_, _>>();
+    let mut previous: Option<(&StatementSyntax, Option<&SpeakerIdentity>)> = None;
+    let mut diagnostics = Vec::new();
+    for node in &analysis.document.nodes {
+        let statement = match node {
+            SyntaxNode::Statement(statement) => statement,
+            node if matches!(node, SyntaxNode::Blank(_)) || node.is_comment() => continue,
+            _ => {
+                previous = None;
+                continue;
+            }
+        };
+        let speaker = speakers.get(&statement.range).copied();
+        if let Some((last, last_speaker)) = previous
+            && last.kind == statement.kind
+            && last_speaker == speaker
+            && !statement.body.source.trim().is_empty()
+            && last.body.source.trim() == statement.body.source.trim()
+        {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Warning,
+                    DiagnosticPhase::Semantic,
+                    "message repeats the previous message from the same speaker",
+                    Some(statement.body.range),
+                )
+                .with_label(last.body.range, "previous message"),
+            );
+        }
+        previous = Some((statement, speaker));
+    }
+    diagnostics
+}
+
+/// Warns when one of several speakers has more than the configured share of
+/// the spoken characters.
+fn speaker_balance(analysis: &AnalyzedDocument, config: &LintConfig) -> Vec<Diagnostic> {
+    let report = speaker_report(&analysis.document, &analysis.actors);
+    if report.len() < 2 {
+        return Vec::new();
+    }
+    report
+        .iter()
+        .filter(|stats| stats.share * 100.0 > f64::from(config.max_speaker_share))
+        .map(|stats| {
+            Diagnostic::new(
+                Severity::Warning,
+                DiagnosticPhase::Semantic,
+                format!(
+                    "speaker '{}' has {:.0}% of the dialogue, above the {}% limit",
+                    stats.name,
+                    stats.share * 100.0,
+                    config.max_speaker_share
+                ),
+                Some(stats.first_range),
+            )
+        })
+        .collect()
+}
+
+/// Warns when an unfenced statement body spans more non-blank continuation
+/// lines than configured. Fenced bodies are the intended multi-line form.
+fn continuation_lines(analysis: &AnalyzedDocument, config: &LintConfig) -> Vec<Diagnostic> {
+    statements(&analysis.document)
+        // A closing fence is the only thing after the body of a statement.
+        .filter(|statement| statement.body.range.end == statement.range.end)
+        .filter_map(|statement| {
+            let continuations = statement
+                .body
+                .source
+                .lines()
+                .skip(1)
+                .filter(|line| !line.trim().is_empty())
+                .count();
+            (continuations > config.max_continuation_lines).then(|| {
+                Diagnostic::new(
+                    Severity::Warning,
+                    DiagnosticPhase::Semantic,
+                    format!(
+                        "message has {continuations} continuation line(s), above the limit of {}",
+                        config.max_continuation_lines
+                    ),
+                    Some(statement.body.range),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Reports statements without a current actor that fall back to a built-in
+/// speaker such as `__Sensei`.
+fn builtin_fallbacks(analysis: &AnalyzedDocument) -> Vec<Diagnostic> {
+    let kinds = statements(&analysis.document)
+        .map(|statement| (statement.range, statement.kind))
+        .collect::<HashMap<_, _>>();
+    analysis
+        .actors
+        .speakers
+        .iter()
+        .filter_map(|speaker| {
+            let SpeakerIdentity::Builtin(builtin) = &speaker.speaker else {
+                return None;
+            };
+            let kind = kinds.get(&speaker.statement_range)?;
+            Some(Diagnostic::new(
+                Severity::Warning,
+                DiagnosticPhase::Semantic,
+                format!(
+                    "{} dialogue has no current actor and falls back to built-in speaker '{}'",
+                    side_name(*kind),
+                    builtin.0
+                ),
+                Some(speaker.statement_range),
+            ))
+        })
+        .collect()
+}
+
+fn statements(document: &SyntaxDocument) -> impl Iterator<Item = &StatementSyntax> {
+    document.nodes.iter().filter_map(|node| match node {
+        SyntaxNode::Statement(statement) => Some(statement),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::analyze_text;
+    use crate::semantic::{CharacterPreset, StaticPresetCatalog};
+
+    fn catalog() -> StaticPresetCatalog {
+        StaticPresetCatalog::new(
+            ["A", "B"]
+                .map(|name| CharacterPreset::new(format!("test::{name}"), [name]))
+                .to_vec(),
+        )
+    }
+
+    fn lint(source: &str, code: &str) -> Vec<Diagnostic> {
+        let analysis = analyze_text(source, &catalog());
+        assert!(analysis.document.diagnostics.is_empty());
+        assert!(analysis.actors.diagnostics.is_empty());
+        lint_document(
+            &analysis,
+            &LintConfig::default().with_rule(code, LintLevel::Warn),
+        )
+    }
+
+    #[test]
+    fn rules_are_allowed_unless_configured() {
+        let analysis = analyze_text("> A: 你好\n< A: 你好", &catalog());
+
+        assert!(lint_document(&analysis, &LintConfig::default()).is_empty());
+        assert_eq!(
+            lint_codes().collect::<Vec<_>>(),
+            vec![
+                "side-flip",
+                "duplicate-message",
+                "speaker-balance",
+                "continuation-lines",
+                "builtin-fallback"
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_messages_need_the_same_speaker_side_and_body() {
+        let source = "> A: 收到\n\n> 收到\n< 收到\n> B: 收到\n@typ: x\n> B: 收到";
+        let diagnostics = lint(source, "duplicate-message");
+
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].code, Some("duplicate-message"));
+        assert_eq!(
+            diagnostics[0].range.map(|range| range.start),
+            source.find("> 收到").map(|start| start + 2)
+        );
+        assert_eq!(diagnostics[0].labels[0].range.start, 5);
+    }
+
+    #[test]
+    fn side_flip_rule_labels_the_previous_side() {
+        let source = "> A: 你好\n< A: 换边了";
+        let diagnostics = lint(source, "side-flip");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(
+            diagnostics[0].message,
+            "actor 'A' switches to right-side dialogue after speaking as left-side dialogue"
+        );
+        assert_eq!(diagnostics[0].labels[0].range.start, 0);
+    }
+
+    #[test]
+    fn speaker_balance_uses_the_configured_share() {
+        let source = "> A: 一二三四五六七八九\n< B: 十";
+        let analysis = analyze_text(source, &catalog());
+        let config = LintConfig::default().with_rule("speaker-balance", LintLevel::Warn);
+
+        let diagnostics = lint_document(&analysis, &config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "speaker 'A' has 90% of the dialogue, above the 80% limit"
+        );
+        assert!(lint_document(&analysis, &config.with_max_speaker_share(90)).is_empty());
+        let allowed = LintConfig::default()
+            .with_rule("speaker-balance", LintLevel::Allow)
+            .with_rule_enabled("speaker-balance");
+        assert_eq!(allowed.level("speaker-balance"), LintLevel::Allow);
+        assert!(lint("> A: 独白\n> A: 还是独白", "speaker-balance").is_empty());
+    }
+
+    #[test]
+    fn builtin_fallback_reports_statements_without_a_current_actor() {
+        let source = "< 早上好\n< B: 你好\n< 在吗";
+        let diagnostics = lint(source, "builtin-fallback");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "right-side dialogue has no current actor and falls back to built-in speaker '__Sensei'"
+        );
+        assert_eq!(diagnostics[0].range.map(|range| range.start), Some(0));
+    }
+
+    #[test]
+    fn continuation_lines_respect_the_configured_limit() {
+        let source = "> A: 第一句\n第二句\n\n第三句\n> A: 单独一句\n> A: \"\"\"\n一\n二\n\"\"\"";
+        let analysis = analyze_text(source, &catalog());
+        let config = LintConfig::default().with_rule("continuation-lines", LintLevel::Warn);
+
+        let diagnostics = lint_document(&analysis, &config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "message has 2 continuation line(s), above the limit of 0"
+        );
+        assert!(lint_document(&analysis, &config.with_max_continuation_lines(2)).is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+//! Translatable text extraction and source-preserving write-back.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::analysis::node_ids;
+use crate::source::TextRange;
+use crate::syntax::{
+    BodyPartSyntax, BodySyntax, SpeakerMarkerSyntax, StatementKind, SyntaxDocument, SyntaxNode,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractedStringKind {
+    Dialogue,
+    Narration,
+    ReplyItem,
+    Bond,
+}
+
+/// One text run of a dialogue body. Inline macros split a body into several
+/// runs and are never part of an extracted string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedString {
+    /// Stable across unrelated edits; built from the node id of
+    /// [`crate::AnalysisOptions::emit_node_ids`] plus the item and part index.
+    pub key: String,
+    pub kind: ExtractedStringKind,
+    /// Raw speaker reference for explicitly attributed dialogue.
+    pub speaker: Option<String>,
+    /// Text as the reader sees it: reply items have `\|` unescaped to `|`.
+    pub source: String,
+    pub range: TextRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalizeError {
+    UnknownKey(String),
+    /// The same key was given more than one translation.
+    DuplicateKey(String),
+    StructureChanged,
+}
+
+impl fmt::Display for LocalizeError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocalizeError::UnknownKey(key) => write!(formatter, "unknown string key '{key}'"),
+            LocalizeError::DuplicateKey(key) => {
+                write!(formatter, "string key '{key}' is translated more than once")
+            }
+            LocalizeError::StructureChanged => {
+                formatter.write_str("translations change the document structure")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LocalizeError {}
+
+pub fn extract_strings(document: &SyntaxDocument, source: &str) -> Vec<ExtractedString> {
+    let mut strings = Vec::new();
+    for (node, node_id) in document.nodes.iter().zip(node_ids(&document.nodes, source)) {
+        match node {
+            SyntaxNode::Statement(statement) => {
+                let kind = match statement.kind {
+                    StatementKind::Narration => ExtractedStringKind::Narration,
+                    StatementKind::Left | StatementKind::Right => ExtractedStringKind::Dialogue,
+                };
+                let speaker = match &statement.marker {
+                    Some(SpeakerMarkerSyntax::Explicit { raw, .. }) => Some(raw.clone()),
+                    _ => None,
+                };
+                push_body(&mut strings, &node_id, 0, &statement.body, kind, speaker);
+            }
+            SyntaxNode::Reply(reply) => {
+                for (index, item) in reply.items.iter().enumerate() {
+                    let kind = ExtractedStringKind::ReplyItem;
+                    push_body(&mut strings, &node_id, index, item, kind, None);
+                }
+            }
+            SyntaxNode::Bond(bond) => {
+                let kind = ExtractedStringKind::Bond;
+                push_body(&mut strings, &node_id, 0, &bond.body, kind, None);
+            }
+            _ => {}
+        }
+    }
+    strings
+}
+
+/// Rewrites the extracted text runs named by `translations` and leaves every
+/// other byte of `source` untouched. A `|` in a reply item is written back
+/// escaped, so it stays part of that item. Fails instead of producing a
+/// script that parses differently: every node must keep its kind, statement
+/// side, speaker marker and patch, and every body its sequence of text runs
+/// and inline macros, so a translation cannot start a statement, name a
+/// speaker, add a reply item or inject a macro.
+pub fn apply_strings(
+    source: &str,
+    document: &SyntaxDocument,
+    translations: &[(String, String)],
+) -> Result<String, LocalizeError> {
+    let ranges = extract_strings(document, source)
+        .into_iter()
+        .map(|string| (string.key, (string.range, string.kind)))
+        .collect::<HashMap<_, _>>();
+    let mut seen = HashSet::new();
+    if let Some((key, _)) = translations.iter().find(|(key, _)| !seen.insert(key)) {
+        return Err(LocalizeError::DuplicateKey(key.clone()));
+    }
+    let mut edits = translations
+        .iter()
+        .map(|(key, text)| {
+            ranges
+                .get(key)
+                .map(|(range, kind)| match kind {
+                    ExtractedStringKind::ReplyItem => (*range, text.replace('|', "\\|")),
+                    _ => (*range, text.clone()),
+                })
+                .ok_or_else(|| LocalizeError::UnknownKey(key.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+
+    let mut translated = source.to_string();
+    for (range, text) in edits {
+        translated.replace_range(range.start..range.end, &text);
+    }
+
+    let reparsed = crate::parse_text(&translated);
+    let shape =
+        |document: &SyntaxDocument| document.nodes.iter().map(node_shape).collect::<Vec<_>>();
+    if shape(&reparsed) != shape(document) {
+        return Err(LocalizeError::StructureChanged);
+    }
+    Ok(translated)
+}
+
+/// Structure of a node with ranges and text left out.
+#[derive(Debug, PartialEq, Eq)]
+struct NodeShape {
+    node: std::mem::Discriminant<SyntaxNode>,
+    statement: Option<(StatementKind, Option<MarkerShape>, bool)>,
+    /// One entry per body, `true` for each text run and `false` for each
+    /// inline macro.
+    bodies: Vec<Vec<bool>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum MarkerShape {
+    Explicit(String),
+    BackRef(u32),
+    UniqueIndex(u32),
+}
+
+fn node_shape(node: &SyntaxNode) -> NodeShape {
+    let parts = |body: &BodySyntax| {
+        body.parts
+            .iter()
+            .map(|part| matches!(part, BodyPartSyntax::Text { .. }))
+            .collect::<Vec<_>>()
+    };
+    let (statement, bodies) = match node {
+        SyntaxNode::Statement(statement) => {
+            let marker = statement.marker.as_ref().map(|marker| match marker {
+                SpeakerMarkerSyntax::Explicit { raw, .. } => MarkerShape::Explicit(raw.clone()),
+                SpeakerMarkerSyntax::BackRef { n, .. } => MarkerShape::BackRef(*n),
+                SpeakerMarkerSyntax::UniqueIndex { n, .. } => MarkerShape::UniqueIndex(*n),
+            });
+            (
+                Some((statement.kind, marker, statement.patch.is_some())),
+                vec![parts(&statement.body)],
+            )
+        }
+        SyntaxNode::Reply(reply) => (None, reply.items.iter().map(parts).collect()),
+        SyntaxNode::Bond(bond) => (None, vec![parts(&bond.body)]),
+        _ => (None, Vec::new()),
+    };
+    NodeShape {
+        node: std::mem::discriminant(node),
+        statement,
+        bodies,
+    }
+}
+
+fn push_body(
+    strings: &mut Vec<ExtractedString>,
+    node_id: &str,
+    item: usize,
+    body: &BodySyntax,
+    kind: ExtractedStringKind,
+    speaker: Option<String>,
+) {
+    for (index, part) in body.parts.iter().enumerate() {
+        let BodyPartSyntax::Text { source, range } = part else {
+            continue;
+        };
+        if source.trim().is_empty() {
+            continue;
+        }
+        strings.push(ExtractedString {
+            key: format!("{node_id}:{item}:{index}"),
+            kind,
+            speaker: speaker.clone(),
+            source: match kind {
+                ExtractedStringKind::ReplyItem => source.replace("\\|", "|"),
+                _ => source.clone(),
+            },
+            range: *range,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_text;
+
+    #[test]
+    fn extracts_text_runs_without_inline_macros() {
+        let source = "> 柚子: 你好 [:#1:] 再见\n- 旁白\n@reply\n- 是\n- 否\n@end";
+        let document = parse_text(source);
+        let strings = extract_strings(&document, source);
+
+        assert_eq!(
+            strings
+                .iter()
+                .map(|string| (string.kind, string.source.trim()))
+                .collect::<Vec<_>>(),
+            vec![
+                (ExtractedStringKind::Dialogue, "你好"),
+                (ExtractedStringKind::Dialogue, "再见"),
+                (ExtractedStringKind::Narration, "旁白"),
+                (ExtractedStringKind::ReplyItem, "是"),
+                (ExtractedStringKind::ReplyItem, "否"),
+            ]
+        );
+        assert_eq!(strings[0].speaker.as_deref(), Some("柚子"));
+        for string in &strings {
+            assert_eq!(&source[string.range.start..string.range.end], string.source);
+        }
+    }
+
+    #[test]
+    fn applies_translations_and_keeps_macros() {
+        let source = "> 柚子: 你好 [:#1:]\n- 旁白";
+        let document = parse_text(source);
+        let strings = extract_strings(&document, source);
+        let translations = vec![
+            (strings[0].key.clone(), "Hello ".to_string()),
+            (strings[1].key.clone(), "Narration".to_string()),
+        ];
+
+        assert_eq!(
+            apply_strings(source, &document, &translations).unwrap(),
+            "> 柚子: Hello [:#1:]\n- Narration"
+        );
+    }
+
+    #[test]
+    fn round_trips_pipes_in_reply_items() {
+        let source = "@reply: 是 | 不知道\\|算了";
+        let document = parse_text(source);
+        let strings = extract_strings(&document, source);
+
+        assert_eq!(strings[1].source, "不知道|算了");
+        assert_eq!(
+            apply_strings(
+                source,
+                &document,
+                &[(strings[1].key.clone(), "Dunno|forget it".to_string())]
+            )
+            .unwrap(),
+            "@reply: 是 | Dunno\\|forget it"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_structural_changes() {
+        let source = "- 旁白";
+        let document = parse_text(source);
+        let key = extract_strings(&document, source)[0].key.clone();
+
+        assert_eq!(
+            apply_strings(
+                source,
+                &document,
+                &[("missing".to_string(), "x".to_string())]
+            ),
+            Err(LocalizeError::UnknownKey("missing".to_string()))
+        );
+        assert_eq!(
+            apply_strings(
+                source,
+                &document,
+                &[
+                    (key.clone(), "H".to_string()),
+                    (key.clone(), "Hi".to_string())
+                ]
+            ),
+            Err(LocalizeError::DuplicateKey(key.clone()))
+        );
+        assert_eq!(
+            apply_strings(source, &document, &[(key, "一\n\n> 柚子: 二".to_string())]),
+            Err(LocalizeError::StructureChanged)
+        );
+    }
+
+    #[test]
+    fn rejects_translations_that_add_speakers_reply_items_or_macros() {
+        for (source, translation) in [
+            ("> 你好", "Time: noon"),
+            ("@reply\n- 是\n@end", "Yes\n- Maybe"),
+            ("> 柚子: 你好", "Hi [:#1:]"),
+        ] {
+            let document = parse_text(source);
+            let key = extract_strings(&document, source)[0].key.clone();
+
+            assert_eq!(
+                apply_strings(source, &document, &[(key, translation.to_string())]),
+                Err(LocalizeError::StructureChanged),
+                "{source:?} <- {translation:?}"
+            );
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Platform-neutral materialization coordination.
 
+use std::fmt;
+
 use crate::diag::{Diagnostic, DiagnosticPhase, Severity};
 use crate::emit::MaterializedContent;
 use crate::resolve::{ResolvedResource, ResourceResolution, ResourceTarget};
@@ -22,6 +24,14 @@ impl MaterializeError {
     }
 }
 
+impl fmt::Display for MaterializeError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for MaterializeError {}
+
 pub trait ResourceMaterializer {
     fn materialize(
         &mut self,
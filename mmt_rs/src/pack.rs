@@ -1,6 +1,6 @@
 //! Resource pack v3 manifest model and deterministic logical resolver.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashSet};
 
 use serde::Deserialize;
 
@@ -17,15 +17,15 @@ pub struct PackManifest {
     pub schema: String,
     pub pack: PackMetadata,
     #[serde(default)]
-    pub entities: HashMap<String, Entity>,
+    pub entities: BTreeMap<String, Entity>,
     #[serde(default)]
     pub contributions: Vec<Contribution>,
     #[serde(default)]
-    pub assets: HashMap<String, PackAsset>,
+    pub assets: BTreeMap<String, PackAsset>,
     #[serde(default)]
-    pub thumbnails: HashMap<String, PackAssetSource>,
+    pub thumbnails: BTreeMap<String, PackAssetSource>,
     #[serde(default)]
-    pub storage: HashMap<String, StorageEntry>,
+    pub storage: BTreeMap<String, StorageEntry>,
 }
 
 impl PackManifest {
@@ -68,7 +68,7 @@ pub struct AvatarSlot {
     #[serde(default)]
     pub default: Option<String>,
     #[serde(default)]
-    pub items: HashMap<String, AvatarItem>,
+    pub items: BTreeMap<String, AvatarItem>,
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
@@ -87,7 +87,7 @@ pub struct StickerSlot {
     #[serde(default)]
     pub default: Option<String>,
     #[serde(default)]
-    pub sets: HashMap<String, StickerSet>,
+    pub sets: BTreeMap<String, StickerSet>,
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
@@ -590,11 +590,12 @@ impl CharacterPresetCatalog for PackRegistry {
                     .as_ref()
                     .and_then(|slot| slot.default.as_ref())
                     .map(|variant| format!("{}/avatar/{variant}", entity.canonical_id));
-                CharacterPreset {
-                    id: entity.canonical_id,
-                    names: entity.entity.names.clone(),
-                    display_name: entity.entity.display_name.clone().or(Some(primary)),
-                    avatar,
+                let display_name = entity.entity.display_name.clone().unwrap_or(primary);
+                let preset = CharacterPreset::new(entity.canonical_id, &entity.entity.names)
+                    .with_display_name(display_name);
+                match avatar {
+                    Some(avatar) => preset.with_avatar(avatar),
+                    None => preset,
                 }
             })
             .collect::<Vec<_>>();
@@ -667,7 +668,7 @@ fn validate_slots(
     namespace: &str,
     owner: &str,
     slots: &Slots,
-    storage: &HashMap<String, StorageEntry>,
+    storage: &BTreeMap<String, StorageEntry>,
     errors: &mut Vec<PackValidationError>,
 ) {
     if let Some(avatar) = &slots.avatar {
@@ -1006,6 +1007,30 @@ mod tests {
         assert_eq!(asset.path, "logo.png");
     }
 
+    #[test]
+    fn ambiguity_candidates_follow_manifest_key_order() {
+        let manifest = PackManifest::from_json(
+            r#"{
+              "schema":"mmt-pack.v3",
+              "pack":{"namespace":"ba","name":"BA","version":"1","type":"base"},
+              "entities":{
+                "c":{"names":["同名"]},
+                "a":{"names":["同名"]},
+                "b":{"names":["同名"]}
+              }
+            }"#,
+        )
+        .unwrap();
+        let registry = PackRegistry::new(vec![manifest]).unwrap();
+
+        assert_eq!(
+            registry.resolve("同名"),
+            PresetLookup::Ambiguous {
+                preset_ids: vec!["ba::a".into(), "ba::b".into(), "ba::c".into()],
+            }
+        );
+    }
+
     #[test]
     fn validation_rejects_missing_storage_and_invalid_entity_names() {
         let invalid = PackManifest::from_json(
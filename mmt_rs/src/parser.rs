@@ -1,5 +1,5 @@
-use crate::diag::Diagnostic;
-use crate::inline::{InlineMacroParseError, parse_inline_macro_at_checked};
+use crate::diag::{Diagnostic, DiagnosticPhase, Severity};
+use crate::inline::{InlineMacroParseError, parse_inline_macro_at_checked, unquote};
 use crate::source::{SourceFile, TextRange};
 use crate::syntax::{
     BlankSyntax, BodyMode, BodyPartSyntax, BodySyntax, BondSyntax, DirectiveBlockSyntax,
@@ -19,12 +19,13 @@ pub fn parse_text(text: &str) -> SyntaxDocument {
 }
 
 pub fn parse_document(source: &SourceFile) -> SyntaxDocument {
-    let lines = collect_lines(source.text());
+    let mut diagnostics = Vec::new();
+    let lines = collect_lines(source.text(), &mut diagnostics);
     let parser = Parser {
         source,
         lines,
         index: 0,
-        diagnostics: Vec::new(),
+        diagnostics,
     };
     parser.parse()
 }
@@ -143,7 +144,7 @@ impl Parser<'_> {
         let (marker, first_body, first_body_start) = if kind == StatementKind::Narration {
             (None, rest.to_string(), cursor)
         } else {
-            parse_speaker_and_body(rest, cursor)
+            parse_speaker_and_body(rest, cursor, &mut self.diagnostics)
         };
 
         let mut body_source = first_body;
@@ -163,15 +164,37 @@ impl Parser<'_> {
             };
         }
 
+        // Interior blank lines are paragraph breaks; trailing ones separate
+        // nodes and stay top-level `Blank` nodes.
+        let mut content_len = body_source.len();
+        let mut content_index = self.index;
         while self.index < self.lines.len() {
             let line = self.lines[self.index].clone();
             if is_explicit_top_level_start(line.text) {
                 break;
             }
+            if let Some(warning) = full_width_node_start_warning(&line) {
+                self.diagnostics.push(warning);
+            }
             body_source.push('\n');
             body_source.push_str(line.text);
-            range_end = line.range.end;
             self.index += 1;
+            if !line.text.trim().is_empty() {
+                content_len = body_source.len();
+                content_index = self.index;
+                range_end = line.range.end;
+            }
+        }
+        body_source.truncate(content_len);
+        self.index = content_index;
+
+        if body_source.trim().is_empty() {
+            self.diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                DiagnosticPhase::Syntax,
+                "empty statement body; use an empty fenced body for an intentional empty bubble",
+                Some(TextRange::new(first_line.range.start, range_end)),
+            ));
         }
 
         let body = self.make_body(body_source, TextRange::new(body_start, range_end));
@@ -200,7 +223,10 @@ impl Parser<'_> {
             "reply" => self.parse_reply(header_parts, header),
             "bond" => self.parse_bond(header_parts, header),
             "typ" if header_parts.payload_start.is_none() => {
-                self.parse_content_directive_block(header_parts, header)
+                self.parse_content_directive_block(header_parts, header, BodyMode::TypstRaw)
+            }
+            "comment" if header_parts.payload_start.is_none() => {
+                self.parse_content_directive_block(header_parts, header, BodyMode::TextRaw)
             }
             _ if header_parts.payload_start.is_some() => {
                 self.parse_directive_line(header_parts, header)
@@ -247,20 +273,31 @@ impl Parser<'_> {
         })
     }
 
+    /// Keeps every line up to `@end` verbatim as one body of the given mode.
     fn parse_content_directive_block(
         &mut self,
         header_parts: DirectiveHeader,
         header: Line<'_>,
+        mode: BodyMode,
     ) -> SyntaxNode {
         let mut source = String::new();
         let mut body_start = header.range.end;
         let mut body_end = header.range.end;
         let mut range_end = header.range.end;
+        // A commented-out scene keeps its own blocks, so only the `@end`
+        // matching the comment closes it.
+        let nests = header_parts.name == "comment";
+        let mut depth = 0usize;
         self.index += 1;
 
         while self.index < self.lines.len() {
             let line = self.lines[self.index].clone();
-            if line.text == "@end" {
+            let closes = line.text.trim_end() == "@end";
+            if nests && depth > 0 && closes {
+                depth -= 1;
+            } else if nests && !closes && opens_directive_block(line.text) {
+                depth += 1;
+            } else if closes {
                 range_end = line.range.end;
                 self.index += 1;
                 return SyntaxNode::DirectiveBlock(DirectiveBlockSyntax {
@@ -269,7 +306,7 @@ impl Parser<'_> {
                     head_args: header_parts.head_args,
                     patch: header_parts.patch,
                     items: vec![DirectiveItemSyntax::Body(self.make_body_with_mode(
-                        BodyMode::TypstRaw,
+                        mode,
                         source,
                         TextRange::new(body_start, body_end),
                     ))],
@@ -289,7 +326,7 @@ impl Parser<'_> {
         }
 
         self.diagnostics.push(Diagnostic::syntax_error(
-            "unterminated @typ block, expected @end",
+            format!("unterminated @{} block, expected @end", header_parts.name),
             TextRange::new(header.range.start, range_end),
         ));
         SyntaxNode::DirectiveBlock(DirectiveBlockSyntax {
@@ -298,7 +335,7 @@ impl Parser<'_> {
             head_args: header_parts.head_args,
             patch: header_parts.patch,
             items: vec![DirectiveItemSyntax::Body(self.make_body_with_mode(
-                BodyMode::TypstRaw,
+                mode,
                 source,
                 TextRange::new(body_start, body_end),
             ))],
@@ -402,21 +439,31 @@ impl Parser<'_> {
         let payload = payload.trim_start();
         self.index += 1;
 
-        let items = if payload.is_empty() {
-            Vec::new()
-        } else if let Some((body, _range_end)) =
-            self.try_parse_fenced_body(payload, payload_start, header.range.end)
-        {
-            vec![body]
+        let mut items = Vec::new();
+        let mut selected = None;
+        // A fence left open on the header line makes the whole payload one
+        // multi-line item; fences closed on the line are ordinary items.
+        let open_fence = parse_fence_open(payload, payload_start)
+            .is_some_and(|open| find_fence_close(open.remaining, open.fence_len).is_none());
+        if open_fence {
+            if let Some((body, _range_end)) =
+                self.try_parse_fenced_body(payload, payload_start, header.range.end)
+            {
+                items.push(body);
+            }
         } else {
-            split_reply_items(payload, payload_start)
-                .into_iter()
-                .map(|(source, range)| self.make_body(source, range))
-                .collect()
-        };
+            for (source, range) in split_reply_items(payload, payload_start) {
+                let (source, start) =
+                    self.take_reply_selection(&source, range, items.len(), &mut selected);
+                let range = TextRange::new(start, range.end);
+                let body = self.make_inline_reply_item(source, range);
+                items.push(body);
+            }
+        }
 
         SyntaxNode::Reply(ReplySyntax {
             items,
+            selected,
             patch: header_parts.patch,
             range: header.range,
         })
@@ -424,6 +471,7 @@ impl Parser<'_> {
 
     fn parse_reply_block(&mut self, header_parts: DirectiveHeader, header: Line<'_>) -> SyntaxNode {
         let mut items = Vec::new();
+        let mut selected = None;
         let mut current_item: Option<(String, usize, usize)> = None;
         let mut range_end = header.range.end;
         self.index += 1;
@@ -438,6 +486,7 @@ impl Parser<'_> {
                 self.index += 1;
                 return SyntaxNode::Reply(ReplySyntax {
                     items,
+                    selected,
                     patch: header_parts.patch,
                     range: TextRange::new(header.range.start, range_end),
                 });
@@ -460,6 +509,12 @@ impl Parser<'_> {
                 let raw = &line.text[1..];
                 let body_text = raw.trim_start();
                 let body_start = line.range.start + 1 + raw.len() - body_text.len();
+                let (body_text, body_start) = self.take_reply_selection(
+                    body_text,
+                    TextRange::new(body_start, line.range.end),
+                    items.len(),
+                    &mut selected,
+                );
                 self.index += 1;
                 if let Some((body, item_range_end)) =
                     self.try_parse_fenced_body(body_text, body_start, line.range.end)
@@ -497,11 +552,52 @@ impl Parser<'_> {
         ));
         SyntaxNode::Reply(ReplySyntax {
             items,
+            selected,
             patch: header_parts.patch,
             range: TextRange::new(header.range.start, range_end),
         })
     }
 
+    /// Strips a leading `* ` selection marker and records the item index.
+    fn take_reply_selection<'t>(
+        &mut self,
+        text: &'t str,
+        range: TextRange,
+        index: usize,
+        selected: &mut Option<usize>,
+    ) -> (&'t str, usize) {
+        let Some(rest) = text.strip_prefix('*') else {
+            return (text, range.start);
+        };
+        let item = rest.trim_start();
+        if item.len() == rest.len() || item.is_empty() {
+            return (text, range.start);
+        }
+        if selected.is_some() {
+            self.diagnostics.push(Diagnostic::syntax_error(
+                "reply can mark only one selected item",
+                TextRange::new(range.start, range.start + 1),
+            ));
+        } else {
+            *selected = Some(index);
+        }
+        (item, range.start + text.len() - item.len())
+    }
+
+    fn make_inline_reply_item(&mut self, source: &str, range: TextRange) -> BodySyntax {
+        if let Some(open) = parse_fence_open(source, range.start)
+            && let Some(close) = find_fence_close(open.remaining, open.fence_len)
+            && open.remaining[close + open.fence_len..].trim().is_empty()
+        {
+            return self.make_body_with_mode(
+                open.mode,
+                open.remaining[..close].to_string(),
+                TextRange::new(open.content_start, open.content_start + close),
+            );
+        }
+        self.make_body(source.to_string(), range)
+    }
+
     fn parse_bond(&mut self, header_parts: DirectiveHeader, header: Line<'_>) -> SyntaxNode {
         if header_parts.payload_start.is_some() {
             return self.parse_bond_line(header_parts, header);
@@ -582,7 +678,8 @@ impl Parser<'_> {
     }
 
     fn parse_directive_header(&mut self, header: Line<'_>) -> Option<DirectiveHeader> {
-        let (name, name_range, after_name) = parse_directive_name(header.text, header.range.start)?;
+        let (name, name_range, after_name) =
+            parse_directive_name(header.text, header.range.start, &mut self.diagnostics)?;
         let mut cursor = after_name;
         let mut rest = &self.source.text()[cursor..header.range.end];
         let leading_ws = rest.len() - rest.trim_start().len();
@@ -708,7 +805,7 @@ impl Parser<'_> {
     }
 }
 
-fn collect_lines(text: &str) -> Vec<Line<'_>> {
+fn collect_lines<'a>(text: &'a str, diagnostics: &mut Vec<Diagnostic>) -> Vec<Line<'a>> {
     let mut result = Vec::new();
     let mut offset = 0;
 
@@ -716,9 +813,35 @@ fn collect_lines(text: &str) -> Vec<Line<'_>> {
         let line_text = segment.strip_suffix('\n').unwrap_or(segment);
         let line_text = line_text.strip_suffix('\r').unwrap_or(line_text);
         let end = offset + line_text.len();
+        // Concatenated files carry a BOM per part; only the file's own BOM is
+        // expected. A BOM is stripped only where a node starts: bodies are
+        // rebuilt from line text, so stripping a continuation line would shift
+        // every offset after it.
+        let (line_text, start) = match line_text.strip_prefix(BYTE_ORDER_MARK) {
+            Some(stripped) if offset == 0 || is_explicit_top_level_start(stripped) => {
+                let start = offset + BYTE_ORDER_MARK.len_utf8();
+                if offset > 0 {
+                    diagnostics.push(invisible_character_warning(
+                        BYTE_ORDER_MARK,
+                        TextRange::new(offset, start),
+                    ));
+                }
+                (stripped, start)
+            }
+            Some(_) => {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    DiagnosticPhase::Syntax,
+                    "invisible character U+FEFF in body text",
+                    Some(TextRange::new(offset, offset + BYTE_ORDER_MARK.len_utf8())),
+                ));
+                (line_text, offset)
+            }
+            None => (line_text, offset),
+        };
         result.push(Line {
             text: line_text,
-            range: TextRange::new(offset, end),
+            range: TextRange::new(start, end),
         });
         offset += segment.len();
     }
@@ -742,6 +865,20 @@ fn is_statement_start(text: &str) -> bool {
     )
 }
 
+/// Whether a line is a bare `@name` directive header, which opens a block
+/// that needs its own `@end`.
+fn opens_directive_block(text: &str) -> bool {
+    let Some((_, _, after_name)) = parse_directive_name(text, 0, &mut Vec::new()) else {
+        return false;
+    };
+    let rest = text[after_name..].trim_start();
+    let rest = match parse_patch(rest, 0) {
+        Ok((_, consumed)) if rest.starts_with('(') => rest[consumed..].trim_start(),
+        _ => rest,
+    };
+    !rest.starts_with(':')
+}
+
 fn is_explicit_top_level_start(text: &str) -> bool {
     text.starts_with('@') || is_statement_start(text)
 }
@@ -794,6 +931,7 @@ fn parse_patch(text: &str, absolute_start: usize) -> Result<(PatchSyntax, usize)
 fn parse_speaker_and_body(
     text: &str,
     absolute_start: usize,
+    diagnostics: &mut Vec<Diagnostic>,
 ) -> (Option<SpeakerMarkerSyntax>, String, usize) {
     if parse_fence_open(text, absolute_start).is_some() {
         return (None, text.to_string(), absolute_start);
@@ -822,8 +960,15 @@ fn parse_speaker_and_body(
     let body_leading = body_raw.len() - body_raw.trim_start().len();
     let body_start = absolute_start + colon_offset + 1 + body_leading;
 
+    let marker_raw = if let Some(ch) = marker_raw.chars().find(|ch| is_zero_width(*ch)) {
+        diagnostics.push(invisible_character_warning(ch, marker_range));
+        marker_raw.replace(is_zero_width, "")
+    } else {
+        marker_raw.to_string()
+    };
+
     (
-        Some(parse_speaker_marker(marker_raw, marker_range)),
+        Some(parse_speaker_marker(&marker_raw, marker_range)),
         body_raw.trim_start().to_string(),
         body_start,
     )
@@ -880,6 +1025,17 @@ fn find_speaker_colon(text: &str) -> Option<usize> {
 }
 
 fn parse_speaker_marker(raw: &str, range: TextRange) -> SpeakerMarkerSyntax {
+    // `\_2` and `"~1"` name an actor literally instead of a history reference.
+    if let Some(name) = raw
+        .strip_prefix('\\')
+        .filter(|rest| rest.starts_with(['_', '~']))
+        .map(str::to_string)
+        .or_else(|| unquote(raw, '"'))
+        .or_else(|| unquote(raw, '\''))
+    {
+        return SpeakerMarkerSyntax::Explicit { raw: name, range };
+    }
+
     if let Some(rest) = raw.strip_prefix('_') {
         if rest.is_empty() {
             return SpeakerMarkerSyntax::BackRef { n: 1, range };
@@ -904,27 +1060,80 @@ fn parse_speaker_marker(raw: &str, range: TextRange) -> SpeakerMarkerSyntax {
     }
 }
 
-fn parse_directive_name(text: &str, absolute_start: usize) -> Option<(String, TextRange, usize)> {
+fn parse_directive_name(
+    text: &str,
+    absolute_start: usize,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<(String, TextRange, usize)> {
     let rest = text.strip_prefix('@')?;
+    let mut name = String::new();
     let mut name_end = 0;
+    // Past trailing zero-width characters too, so `@mode\u{200b}: t` still
+    // reaches its payload `:`.
+    let mut consumed = 0;
+    let mut invisible = None;
     for (offset, ch) in rest.char_indices() {
         if ch.is_alphanumeric() || ch == '_' {
+            name.push(ch);
             name_end = offset + ch.len_utf8();
+        } else if is_zero_width(ch) {
+            invisible.get_or_insert(ch);
         } else {
             break;
         }
+        consumed = offset + ch.len_utf8();
     }
-    if name_end == 0 {
+    if name.is_empty() {
         return None;
     }
     let name_start = absolute_start + 1;
-    Some((
-        rest[..name_end].to_string(),
-        TextRange::new(name_start, name_start + name_end),
-        name_start + name_end,
+    let name_range = TextRange::new(name_start, name_start + name_end);
+    if let Some(ch) = invisible {
+        diagnostics.push(invisible_character_warning(ch, name_range));
+    }
+    Some((name, name_range, name_start + consumed))
+}
+
+const BYTE_ORDER_MARK: char = '\u{feff}';
+
+fn is_zero_width(ch: char) -> bool {
+    matches!(ch, '\u{200b}' | '\u{200c}' | '\u{200d}')
+}
+
+/// Scripts typed with a full-width IME start lines with `＞` or `＠`, which
+/// are ordinary text and would otherwise merge silently into the previous
+/// statement.
+fn full_width_node_start_warning(line: &Line<'_>) -> Option<Diagnostic> {
+    let ch = line.text.chars().next()?;
+    let ascii = match ch {
+        '＞' => '>',
+        '＜' => '<',
+        '－' => '-',
+        '＠' => '@',
+        _ => return None,
+    };
+    Some(Diagnostic::new(
+        Severity::Warning,
+        DiagnosticPhase::Syntax,
+        format!(
+            "full-width '{ch}' continues the previous statement; use '{ascii}' to start a new node"
+        ),
+        Some(TextRange::new(
+            line.range.start,
+            line.range.start + ch.len_utf8(),
+        )),
     ))
 }
 
+fn invisible_character_warning(ch: char, range: TextRange) -> Diagnostic {
+    Diagnostic::new(
+        Severity::Warning,
+        DiagnosticPhase::Syntax,
+        format!("invisible character U+{:04X} removed", ch as u32),
+        Some(range),
+    )
+}
+
 fn parse_head_args(
     text: &str,
     absolute_start: usize,
@@ -986,7 +1195,9 @@ fn parse_head_args(
 fn parse_directive_item(line: Line<'_>, diagnostics: &mut Vec<Diagnostic>) -> DirectiveItemSyntax {
     if let Some(colon) = line.text.find(':') {
         let name = line.text[..colon].trim();
+        // `https://...` is a body line, not a field named `https`.
         if !name.is_empty()
+            && !line.text[colon + 1..].starts_with("//")
             && name
                 .chars()
                 .all(|ch| ch.is_alphanumeric() || ch == '_' || ch == '-')
@@ -1076,8 +1287,10 @@ fn parse_body_parts(
                 cursor = parsed.syntax.range.end - absolute_start;
                 parts.push(BodyPartSyntax::InlineMacro(parsed.syntax));
             }
-            Err(InlineMacroParseError::MissingClose { range }) => {
-                diagnostics.push(Diagnostic::syntax_error("unclosed inline macro", range));
+            Err(error) => {
+                if let InlineMacroParseError::MissingClose { range } = error {
+                    diagnostics.push(Diagnostic::syntax_error("unclosed inline macro", range));
+                }
                 parts.push(BodyPartSyntax::Text {
                     source: source[marker_start..marker_start + 2].to_string(),
                     range: TextRange::new(
@@ -1090,13 +1303,6 @@ fn parse_body_parts(
         }
     }
 
-    if source.is_empty() {
-        parts.push(BodyPartSyntax::Text {
-            source: String::new(),
-            range: TextRange::empty(absolute_start),
-        });
-    }
-
     parts
 }
 
@@ -1281,6 +1487,145 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn escaped_and_quoted_speaker_markers_stay_explicit() {
+        let doc = parse_text(
+            "> \\_2: escaped backref\n\
+             > \\~chan: escaped index\n\
+             > \"~1\": quoted\n\
+             > '_': single quoted\n\
+             > \"a: b\": quoted colon\n\
+             > _Prototype: plain underscore name",
+        );
+        assert!(doc.diagnostics.is_empty(), "{:?}", doc.diagnostics);
+
+        let markers = doc
+            .nodes
+            .iter()
+            .map(|node| match node {
+                SyntaxNode::Statement(StatementSyntax {
+                    marker: Some(SpeakerMarkerSyntax::Explicit { raw, .. }),
+                    ..
+                }) => raw.as_str(),
+                _ => panic!("expected explicit speaker marker"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            markers,
+            vec!["_2", "~chan", "~1", "_", "a: b", "_Prototype"]
+        );
+    }
+
+    #[test]
+    fn colons_after_the_speaker_separator_stay_in_the_body() {
+        let doc = parse_text("> 柚子: 12:30 开会\n> : 12:30 开会\n- 12:30 开会");
+        assert!(doc.diagnostics.is_empty(), "{:?}", doc.diagnostics);
+
+        let statements = doc
+            .nodes
+            .iter()
+            .map(|node| match node {
+                SyntaxNode::Statement(statement) => (
+                    match &statement.marker {
+                        Some(SpeakerMarkerSyntax::Explicit { raw, .. }) => Some(raw.as_str()),
+                        _ => None,
+                    },
+                    statement.body.source.as_str(),
+                ),
+                _ => panic!("expected statement"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statements,
+            vec![
+                (Some("柚子"), "12:30 开会"),
+                (None, "12:30 开会"),
+                (None, "12:30 开会"),
+            ]
+        );
+    }
+
+    #[test]
+    fn byte_order_marks_are_stripped_from_every_line() {
+        let doc = parse_text("\u{feff}> 柚子: one\n\u{feff}@mode: t\n\u{feff}< 桃井: two");
+
+        assert_eq!(doc.nodes.len(), 3);
+        assert!(matches!(&doc.nodes[1], SyntaxNode::DirectiveLine(line) if line.name == "mode"));
+        let SyntaxNode::Statement(last) = &doc.nodes[2] else {
+            panic!("expected statement");
+        };
+        assert!(matches!(
+            &last.marker,
+            Some(SpeakerMarkerSyntax::Explicit { raw, .. }) if raw == "桃井"
+        ));
+
+        let messages = doc
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (Severity::Warning, "invisible character U+FEFF removed"),
+                (Severity::Warning, "invisible character U+FEFF removed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn byte_order_marks_in_continuation_lines_keep_body_ranges_exact() {
+        let source = "> 柚子: 第一行\n\u{feff}第二行 [:#1:] 尾";
+        let doc = parse_text(source);
+        let SyntaxNode::Statement(statement) = &doc.nodes[0] else {
+            panic!("expected statement");
+        };
+
+        assert_eq!(
+            &source[statement.body.range.start..statement.body.range.end],
+            statement.body.source
+        );
+        for part in &statement.body.parts {
+            let (text, range) = match part {
+                BodyPartSyntax::Text { source, range } => (source.as_str(), *range),
+                BodyPartSyntax::InlineMacro(syntax) => ("[:#1:]", syntax.range),
+            };
+            assert_eq!(&source[range.start..range.end], text);
+        }
+        assert_eq!(doc.diagnostics.len(), 1);
+        assert_eq!(
+            doc.diagnostics[0].message,
+            "invisible character U+FEFF in body text"
+        );
+    }
+
+    #[test]
+    fn zero_width_characters_are_removed_from_markers_and_directive_names() {
+        let doc =
+            parse_text("> 柚\u{200b}子: 正\u{200b}文\n@mo\u{200d}de: t\n@mode\u{200b}: typst");
+
+        let SyntaxNode::Statement(statement) = &doc.nodes[0] else {
+            panic!("expected statement");
+        };
+        assert!(matches!(
+            &statement.marker,
+            Some(SpeakerMarkerSyntax::Explicit { raw, .. }) if raw == "柚子"
+        ));
+        assert_eq!(statement.body.source, "正\u{200b}文");
+        assert!(matches!(&doc.nodes[1], SyntaxNode::DirectiveLine(line) if line.name == "mode"));
+        assert!(matches!(&doc.nodes[2], SyntaxNode::DirectiveLine(line) if line.name == "mode"));
+        assert_eq!(doc.diagnostics.len(), 3);
+        assert!(
+            doc.diagnostics
+                .iter()
+                .all(|diagnostic| diagnostic.severity == Severity::Warning)
+        );
+        assert_eq!(
+            doc.diagnostics[0].message,
+            "invisible character U+200B removed"
+        );
+    }
+
     #[test]
     fn speaker_separator_ignores_namespaces_urls_and_nested_colons() {
         let doc = parse_text(
@@ -1347,6 +1692,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn colon_directive_payload_keeps_inner_colons() {
+        let doc = parse_text("@title: a:b\n@title(size: 1em): Part 2: https://example.com");
+        assert!(doc.diagnostics.is_empty(), "{:?}", doc.diagnostics);
+
+        let payloads = doc
+            .nodes
+            .iter()
+            .map(|node| match node {
+                SyntaxNode::DirectiveLine(line) => {
+                    assert_eq!(line.name, "title");
+                    line.payload.as_ref().map(|body| body.source.as_str())
+                }
+                _ => panic!("expected directive line"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            payloads,
+            vec![Some("a:b"), Some("Part 2: https://example.com")]
+        );
+    }
+
+    #[test]
+    fn directive_colon_must_directly_follow_name_or_patch() {
+        let doc = parse_text("@title a:b\n@end");
+        assert!(doc.diagnostics.is_empty(), "{:?}", doc.diagnostics);
+
+        let SyntaxNode::DirectiveBlock(block) = &doc.nodes[0] else {
+            panic!("expected directive block");
+        };
+        assert_eq!(block.name, "title");
+        assert_eq!(block.head_args.len(), 1);
+        assert_eq!(block.head_args[0].raw, "a:b");
+    }
+
+    #[test]
+    fn directive_block_fields_split_on_first_colon_but_keep_urls() {
+        let doc =
+            parse_text("@asset hero\nsrc: https://example.com/a:b.png\nhttps://example.com\n@end");
+        let SyntaxNode::DirectiveBlock(block) = &doc.nodes[0] else {
+            panic!("expected directive block");
+        };
+        assert!(matches!(
+            &block.items[0],
+            DirectiveItemSyntax::Field(field)
+                if field.name == "src" && field.value == "https://example.com/a:b.png"
+        ));
+        assert!(matches!(
+            &block.items[1],
+            DirectiveItemSyntax::Body(body) if body.source == "https://example.com"
+        ));
+    }
+
     #[test]
     fn typ_block_preserves_arbitrary_content_as_one_raw_body() {
         let doc = parse_text(
@@ -1423,6 +1821,70 @@ mod tests {
         assert_eq!(second.body.source, "新节点");
     }
 
+    #[test]
+    fn full_width_node_starts_in_continuations_are_warnings() {
+        let source =
+            "> 柚子: 第一行\n＞ 阿露：第二行\n＠reply: 是\n  ＞ 缩进\n> \"\"\"\n＞ 围栏\n\"\"\"";
+        let doc = parse_text(source);
+
+        assert_eq!(doc.nodes.len(), 2);
+        assert_eq!(
+            doc.diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.range.unwrap().start))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "full-width '＞' continues the previous statement; use '>' to start a new node",
+                    source.find('＞').unwrap()
+                ),
+                (
+                    "full-width '＠' continues the previous statement; use '@' to start a new node",
+                    source.find('＠').unwrap()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn statement_body_keeps_interior_blank_lines_but_not_trailing_ones() {
+        let text = "> 柚子: 第一段\n\n第二段\n\n\n< 桃井: 新节点\n  \n";
+        let doc = parse_text(text);
+        assert!(doc.diagnostics.is_empty());
+
+        let kinds = doc
+            .nodes
+            .iter()
+            .map(|node| match node {
+                SyntaxNode::Statement(statement) => statement.body.source.as_str(),
+                SyntaxNode::Blank(_) => "<blank>",
+                _ => panic!("unexpected node"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "第一段\n\n第二段",
+                "<blank>",
+                "<blank>",
+                "新节点",
+                "<blank>"
+            ]
+        );
+
+        let SyntaxNode::Statement(first) = &doc.nodes[0] else {
+            panic!("expected statement");
+        };
+        assert_eq!(
+            &text[first.range.start..first.range.end],
+            "> 柚子: 第一段\n\n第二段"
+        );
+        assert_eq!(
+            &text[first.body.range.start..first.body.range.end],
+            first.body.source
+        );
+    }
+
     #[test]
     fn directive_block_preserves_generic_fields() {
         let doc = parse_text("@actor hifumi\npreset: ba::日富美\nalso-as: [日富美]\n@end");
@@ -1455,6 +1917,87 @@ mod tests {
         assert!(matches!(doc.nodes[0], SyntaxNode::DirectiveBlock(_)));
     }
 
+    #[test]
+    fn stray_end_reports_diagnostic_and_error_node() {
+        let source = "@actor hifumi\n@end\n@end\n> 你好";
+        let doc = parse_text(source);
+
+        assert_eq!(doc.diagnostics.len(), 1);
+        assert!(
+            doc.diagnostics[0]
+                .message
+                .contains("unexpected @end without matching directive block")
+        );
+        assert_eq!(
+            doc.diagnostics[0].range.map(|range| range.start),
+            source.rfind("@end")
+        );
+        assert_eq!(doc.nodes.len(), 3);
+        assert!(matches!(doc.nodes[1], SyntaxNode::Error(_)));
+        assert!(matches!(doc.nodes[2], SyntaxNode::Statement(_)));
+    }
+
+    #[test]
+    fn unterminated_special_blocks_report_diagnostics() {
+        for (source, message) in [
+            ("@reply\n- 是\n- 否", "unterminated reply block"),
+            ("@bond\n羁绊", "unterminated bond block"),
+            ("@typ\n#let x = 1", "unterminated @typ block"),
+            ("@comment\n> 柚子: 旧台词", "unterminated @comment block"),
+        ] {
+            let doc = parse_text(source);
+
+            assert_eq!(doc.diagnostics.len(), 1, "{source}");
+            assert!(doc.diagnostics[0].message.contains(message), "{source}");
+        }
+    }
+
+    #[test]
+    fn node_at_finds_the_node_under_a_byte_offset() {
+        let source = "> 柚子: 你好\n\n@reply\n- 是\n@end";
+        let doc = parse_text(source);
+        let reply_start = source.find("@reply").unwrap();
+
+        assert!(matches!(doc.node_at(0), Some(SyntaxNode::Statement(_))));
+        assert!(matches!(
+            doc.node_at(source.find('\n').unwrap()),
+            Some(SyntaxNode::Statement(_))
+        ));
+        assert!(matches!(
+            doc.node_at(reply_start - 1),
+            Some(SyntaxNode::Blank(_))
+        ));
+        assert!(matches!(
+            doc.node_at(source.len()),
+            Some(SyntaxNode::Reply(_))
+        ));
+        assert!(doc.node_at(source.len() + 1).is_none());
+    }
+
+    #[test]
+    fn comment_blocks_keep_their_lines_verbatim() {
+        let source = "@comment\n> 柚子: 旧台词 [:#1:]\n@reply\n- 是\n@end\n\
+                      @actor(x: 1) 柚子\nnickname: 柚\n@end\n@typ: #let a = 1\n@end \n< 桃井: 新台词";
+        let doc = parse_text(source);
+
+        assert!(doc.diagnostics.is_empty());
+        assert_eq!(doc.nodes.len(), 2);
+        assert!(doc.nodes[0].is_comment());
+        let SyntaxNode::DirectiveBlock(block) = &doc.nodes[0] else {
+            panic!("expected comment block");
+        };
+        let [DirectiveItemSyntax::Body(body)] = block.items.as_slice() else {
+            panic!("expected one comment body");
+        };
+        assert_eq!(body.mode, BodyMode::TextRaw);
+        assert_eq!(
+            body.source,
+            "> 柚子: 旧台词 [:#1:]\n@reply\n- 是\n@end\n\
+             @actor(x: 1) 柚子\nnickname: 柚\n@end\n@typ: #let a = 1"
+        );
+        assert!(matches!(&doc.nodes[1], SyntaxNode::Statement(_)));
+    }
+
     #[test]
     fn nested_directive_inside_block_is_visible_error() {
         let doc = parse_text("@actor hifumi\n@asset\n@end");
@@ -1531,6 +2074,23 @@ mod tests {
         };
         assert!(statement.body.source.is_empty());
         assert!(statement.body.range.is_empty());
+        assert!(statement.body.parts.is_empty());
+    }
+
+    #[test]
+    fn empty_unfenced_statement_body_is_a_warning() {
+        let doc = parse_text("> 柚子:\n< 桃井:   \n\n- ");
+
+        assert_eq!(doc.nodes.len(), 4);
+        assert_eq!(doc.diagnostics.len(), 3);
+        assert!(doc.diagnostics.iter().all(|diagnostic| {
+            diagnostic.severity == Severity::Warning
+                && diagnostic.message.starts_with("empty statement body")
+        }));
+        let SyntaxNode::Statement(first) = &doc.nodes[0] else {
+            panic!("expected statement");
+        };
+        assert!(first.body.parts.is_empty());
     }
 
     #[test]
@@ -1608,6 +2168,56 @@ mod tests {
         assert_eq!(reply.items[2].source, "不知道\\|算了");
     }
 
+    #[test]
+    fn inline_reply_items_can_be_fenced_and_selected() {
+        let doc = parse_text("@reply: 是 | * \"\"\"也许|之后\"\"\" | rt\"\"\"[:#1:]\"\"\"");
+        assert!(doc.diagnostics.is_empty(), "{:?}", doc.diagnostics);
+
+        let SyntaxNode::Reply(reply) = &doc.nodes[0] else {
+            panic!("expected reply");
+        };
+        assert_eq!(reply.selected, Some(1));
+        assert_eq!(
+            reply
+                .items
+                .iter()
+                .map(|item| (item.source.as_str(), item.mode))
+                .collect::<Vec<_>>(),
+            vec![
+                ("是", BodyMode::Inherit),
+                ("也许|之后", BodyMode::Inherit),
+                ("[:#1:]", BodyMode::TextRaw),
+            ]
+        );
+        assert!(matches!(
+            &reply.items[0].parts[..],
+            [BodyPartSyntax::Text { source, .. }] if source == "是"
+        ));
+    }
+
+    #[test]
+    fn reply_block_selection_marker_and_duplicates() {
+        let doc = parse_text("@reply\n- 是\n- * 否 [:#1:]\n  继续\n- * 再选\n- *强调*\n@end");
+
+        assert_eq!(doc.diagnostics.len(), 1);
+        assert!(
+            doc.diagnostics[0]
+                .message
+                .contains("only one selected item")
+        );
+        let SyntaxNode::Reply(reply) = &doc.nodes[0] else {
+            panic!("expected reply");
+        };
+        assert_eq!(reply.selected, Some(1));
+        assert_eq!(reply.items[1].source, "否 [:#1:]\n  继续");
+        assert!(matches!(
+            reply.items[1].parts[1],
+            BodyPartSyntax::InlineMacro(_)
+        ));
+        assert_eq!(reply.items[2].source, "再选");
+        assert_eq!(reply.items[3].source, "*强调*");
+    }
+
     #[test]
     fn reply_block_uses_explicit_items_and_continuations() {
         let doc = parse_text("@reply\n- 12\n34\n- \"\"\"\n也许 | 之后再说\n\"\"\"\n@end");
@@ -1690,4 +2300,49 @@ mod tests {
                 .contains("unclosed inline macro render patch")
         );
     }
+
+    #[test]
+    fn bracket_heavy_text_survives_verbatim_in_text_mode() {
+        let corpus = [
+            "(｀・ω・´)ゞ",
+            "[:3」∠]",
+            "(╯°□°）╯︵ ┻━┻ []~(￣▽￣)~*",
+            "见 [文档](https://example.com/a_(b)) 和 ![图](c.png)",
+            "xs[:n] + ys[::2] + grid[:, 0]",
+            "f(a[:i)] {\"k\": [1, [2]]}",
+        ];
+        for text in corpus {
+            let doc = parse_text(&format!("> 柚子: {text}"));
+            let SyntaxNode::Statement(statement) = &doc.nodes[0] else {
+                panic!("expected statement for {text:?}");
+            };
+
+            assert!(
+                doc.diagnostics.is_empty(),
+                "{text:?}: {:?}",
+                doc.diagnostics
+            );
+            assert_eq!(
+                statement
+                    .body
+                    .parts
+                    .iter()
+                    .map(|part| match part {
+                        BodyPartSyntax::Text { source, .. } => source.as_str(),
+                        BodyPartSyntax::InlineMacro(_) => panic!("macro in {text:?}"),
+                    })
+                    .collect::<String>(),
+                text
+            );
+        }
+
+        let doc = parse_text("> 柚子: xs[:n] [:#1:]");
+        let SyntaxNode::Statement(statement) = &doc.nodes[0] else {
+            panic!("expected statement");
+        };
+        assert!(matches!(
+            statement.body.parts.last(),
+            Some(BodyPartSyntax::InlineMacro(_))
+        ));
+    }
 }
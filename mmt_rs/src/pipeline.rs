@@ -1,7 +1,10 @@
 //! End-to-end language-core orchestration without platform-specific I/O.
 
+use std::fmt;
+
 use crate::diag::{Diagnostic, Severity};
 use crate::emit::{EmitOptions, EmittedTypst, emit_typst};
+use crate::lint::lint_document;
 use crate::materialize::{Materialization, ResourceMaterializer, materialize_resources};
 use crate::pack::PackRegistry;
 use crate::resolve::{ResourceResolution, resolve_actor_avatars, resolve_resources};
@@ -81,6 +84,7 @@ pub(crate) fn compile_analyzed(
         emit_options,
     );
     validate_generated_typst(&mut typst);
+    let lints = lint_document(analysis, &emit_options.lints);
     let diagnostics = [
         analysis.document.diagnostics.as_slice(),
         analysis.document_config.diagnostics.as_slice(),
@@ -88,6 +92,7 @@ pub(crate) fn compile_analyzed(
         analysis.actors.diagnostics.as_slice(),
         analysis.assets.diagnostics.as_slice(),
         analysis.resource_markers.diagnostics.as_slice(),
+        lints.as_slice(),
         resolution.diagnostics.as_slice(),
         materialization.diagnostics.as_slice(),
         typst.diagnostics.as_slice(),
@@ -122,6 +127,26 @@ pub struct CompilationFailure {
     pub diagnostics: Vec<Diagnostic>,
 }
 
+impl fmt::Display for CompilationFailure {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut errors = self
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error);
+        let Some(first) = errors.next() else {
+            return formatter.write_str("compilation failed");
+        };
+        write!(formatter, "compilation failed: {}", first.message)?;
+        match errors.count() {
+            0 => Ok(()),
+            1 => formatter.write_str(" (and 1 more error)"),
+            more => write!(formatter, " (and {more} more errors)"),
+        }
+    }
+}
+
+impl std::error::Error for CompilationFailure {}
+
 pub fn compile_text(
     source: &str,
     packs: &PackRegistry,
@@ -162,6 +187,7 @@ pub fn compile_text_strict(
 ) -> Result<Compilation, CompilationFailure> {
     let analysis = analyze_text_with_pack(source, packs);
     fail_if_errors(analysis.document.diagnostics.clone())?;
+    let lints = lint_document(&analysis, &emit_options.lints);
     fail_if_errors(
         [
             analysis.document_config.diagnostics.as_slice(),
@@ -169,6 +195,7 @@ pub fn compile_text_strict(
             analysis.actors.diagnostics.as_slice(),
             analysis.assets.diagnostics.as_slice(),
             analysis.resource_markers.diagnostics.as_slice(),
+            lints.as_slice(),
         ]
         .into_iter()
         .flatten()
@@ -199,6 +226,7 @@ pub fn compile_text_strict(
         analysis.actors.diagnostics.as_slice(),
         analysis.assets.diagnostics.as_slice(),
         analysis.resource_markers.diagnostics.as_slice(),
+        lints.as_slice(),
         resolution.diagnostics.as_slice(),
         materialization.diagnostics.as_slice(),
         typst.diagnostics.as_slice(),
@@ -258,6 +286,7 @@ fn validate_generated_typst(typst: &mut EmittedTypst) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lint::{LintConfig, LintLevel};
     use crate::materialize::{MaterializeError, MaterializedImage};
     use crate::pack::PackManifest;
     use crate::resolve::{ResolvedResource, ResolvedResourceKind};
@@ -356,6 +385,56 @@ mod tests {
         assert_eq!(materializer.calls, 0);
     }
 
+    #[test]
+    fn strict_compilation_failure_is_a_std_error() {
+        let packs = PackRegistry::new(vec![PackManifest::from_json(PACK).unwrap()]).unwrap();
+        let failure = compile_text_strict(
+            "@end\n@end",
+            &packs,
+            &mut FakeMaterializer,
+            &EmitOptions::default(),
+        )
+        .unwrap_err();
+        let error: Box<dyn std::error::Error> = Box::new(failure);
+
+        assert_eq!(
+            error.to_string(),
+            "compilation failed: unexpected @end without matching directive block \
+             (and 1 more error)"
+        );
+    }
+
+    #[test]
+    fn strict_compilation_fails_only_on_denied_lints() {
+        let packs = PackRegistry::new(vec![PackManifest::from_json(PACK).unwrap()]).unwrap();
+        let source = "> 柚子: 早\n> 早";
+        let compile = |options: &EmitOptions| {
+            compile_text_strict(source, &packs, &mut FakeMaterializer, options)
+        };
+
+        assert!(
+            compile(&EmitOptions::default())
+                .unwrap()
+                .diagnostics
+                .is_empty()
+        );
+        let warned = compile(
+            &EmitOptions::default()
+                .with_lints(LintConfig::default().with_rule("duplicate-message", LintLevel::Warn)),
+        )
+        .unwrap();
+        assert_eq!(warned.diagnostics.len(), 1);
+        assert_eq!(warned.diagnostics[0].code, Some("duplicate-message"));
+
+        let failure = compile(
+            &EmitOptions::default()
+                .with_lints(LintConfig::default().with_rule("duplicate-message", LintLevel::Deny)),
+        )
+        .unwrap_err();
+        assert_eq!(failure.diagnostics.len(), 1);
+        assert_eq!(failure.diagnostics[0].severity, Severity::Error);
+    }
+
     #[test]
     fn strict_compilation_returns_complete_valid_pipeline() {
         let packs = PackRegistry::new(vec![PackManifest::from_json(PACK).unwrap()]).unwrap();
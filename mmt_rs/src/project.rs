@@ -109,14 +109,13 @@ impl ProjectMaterializer {
                 source.pack_namespace, source.storage_id, source.storage.kind
             )));
         }
-        let base = source.storage.base.as_deref().unwrap_or("");
-        let path = source.path.as_deref().ok_or_else(|| {
+        let path = source.file_path().ok_or_else(|| {
             MaterializeError::new(format!(
                 "image-dir storage '{}::{}' requires a resource path",
                 source.pack_namespace, source.storage_id
             ))
         })?;
-        self.checked_pack_path(source, &Path::new(base).join(path))
+        self.checked_pack_path(source, Path::new(&path))
     }
 
     fn materialize_sequence(
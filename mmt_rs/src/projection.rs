@@ -186,7 +186,9 @@ impl ProjectionIndex {
                 }
                 if mapping == MappingMode::Escaped
                     && mmt_source.get(range.start..range.end)
-                        == emitted.source.get(entry.generated_range.start..entry.generated_range.end)
+                        == emitted
+                            .source
+                            .get(entry.generated_range.start..entry.generated_range.end)
                 {
                     mapping = MappingMode::Identity;
                 }
@@ -645,12 +647,9 @@ mod tests {
     use crate::semantic::{CharacterPreset, StaticPresetCatalog};
 
     fn catalog() -> StaticPresetCatalog {
-        StaticPresetCatalog::new(vec![CharacterPreset {
-            id: "ba::柚子".to_string(),
-            names: vec!["柚子".to_string()],
-            display_name: None,
-            avatar: Some("avatar/default".to_string()),
-        }])
+        StaticPresetCatalog::new(vec![
+            CharacterPreset::new("ba::柚子", ["柚子"]).with_avatar("avatar/default"),
+        ])
     }
 
     #[test]
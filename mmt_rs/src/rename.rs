@@ -0,0 +1,324 @@
+//! Source-preserving rename of script actor names.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::inline::{
+    DeclarationValueSyntax, InlineMacroSyntax, MacroValueSyntax, QuoteKind, parse_declaration_value,
+};
+use crate::semantic::{
+    CharacterPresetCatalog, PresetLookup, ResolvedBodyMode, lower_actors, lower_resource_markers,
+    resolve_body_modes,
+};
+use crate::source::TextRange;
+use crate::syntax::{
+    BodyPartSyntax, BodySyntax, DirectiveItemSyntax, SpeakerMarkerSyntax, SyntaxDocument,
+    SyntaxNode,
+};
+use crate::typst_check::scan_typst_overlay_macros;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// The new name cannot be written bare in every position a name occupies.
+    InvalidName(String),
+    /// No `@actor` block in the script declares the old name.
+    UndeclaredName(String),
+    /// The new name already resolves to an actor or a character preset.
+    NameInUse(String),
+    /// Renaming would bind some statement or resource to a different actor.
+    ChangesMeaning,
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::InvalidName(name) => write!(formatter, "invalid actor name '{name}'"),
+            RenameError::UndeclaredName(name) => {
+                write!(formatter, "actor name '{name}' is not declared by @actor")
+            }
+            RenameError::NameInUse(name) => write!(formatter, "actor name '{name}' is in use"),
+            RenameError::ChangesMeaning => {
+                formatter.write_str("rename would change which actor a reference resolves to")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// Renames an actor name declared by `@actor` everywhere the script uses it:
+/// explicit speaker markers, the `@actor` head, `also-as` items, the subject
+/// of `[:name, selector:]` markers and bare `name/sticker/...` resource paths. Other aliases, preset references and
+/// body text are left untouched.
+///
+/// The result is lowered again and rejected unless every statement and
+/// resource marker still resolves to the same actor.
+pub fn rename_actor(
+    source: &str,
+    old: &str,
+    new: &str,
+    catalog: &impl CharacterPresetCatalog,
+) -> Result<String, RenameError> {
+    if !is_plain_name(new) {
+        return Err(RenameError::InvalidName(new.to_string()));
+    }
+    let document = crate::parse_text(source);
+    let before = lower_actors(&document, catalog);
+    if before
+        .actors
+        .iter()
+        .any(|actor| actor.names.iter().any(|name| name == new))
+        || !matches!(catalog.resolve(new), PresetLookup::Missing)
+    {
+        return Err(RenameError::NameInUse(new.to_string()));
+    }
+
+    let mut collector = EditCollector {
+        old,
+        new,
+        declared: false,
+        edits: Vec::new(),
+    };
+    collector.collect(&document);
+    if !collector.declared {
+        return Err(RenameError::UndeclaredName(old.to_string()));
+    }
+    let mut edits = collector.edits;
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut renamed = source.to_string();
+    for (range, text) in edits {
+        renamed.replace_range(range.start..range.end, &text);
+    }
+
+    let renamed_document = crate::parse_text(&renamed);
+    let after = lower_actors(&renamed_document, catalog);
+    let bindings = |document: &SyntaxDocument, actors: &crate::semantic::ActorLowering| {
+        let modes = resolve_body_modes(document);
+        let resources = lower_resource_markers(document, &modes, actors);
+        (
+            actors
+                .speakers
+                .iter()
+                .map(|speaker| (speaker.speaker.clone(), speaker.revision))
+                .collect::<Vec<_>>(),
+            resources
+                .markers
+                .into_iter()
+                .map(|marker| marker.selector)
+                .collect::<Vec<_>>(),
+            actors.diagnostics.len() + resources.diagnostics.len(),
+        )
+    };
+    if renamed_document.diagnostics.len() != document.diagnostics.len()
+        || bindings(&renamed_document, &after) != bindings(&document, &before)
+    {
+        return Err(RenameError::ChangesMeaning);
+    }
+    Ok(renamed)
+}
+
+struct EditCollector<'a> {
+    old: &'a str,
+    new: &'a str,
+    declared: bool,
+    edits: Vec<(TextRange, String)>,
+}
+
+impl EditCollector<'_> {
+    fn collect(&mut self, document: &SyntaxDocument) {
+        let modes = resolve_body_modes(document)
+            .bodies
+            .into_iter()
+            .map(|entry| (entry.range, entry.mode))
+            .collect::<HashMap<_, _>>();
+        for node in &document.nodes {
+            match node {
+                SyntaxNode::Statement(statement) => {
+                    if let Some(SpeakerMarkerSyntax::Explicit { raw, range }) = &statement.marker
+                        && raw == self.old
+                    {
+                        self.edits.push((*range, self.new.to_string()));
+                    }
+                    self.collect_body(&statement.body, &modes);
+                }
+                SyntaxNode::Reply(reply) => {
+                    for item in &reply.items {
+                        self.collect_body(item, &modes);
+                    }
+                }
+                SyntaxNode::Bond(bond) => self.collect_body(&bond.body, &modes),
+                SyntaxNode::DirectiveBlock(block) if block.name == "actor" => {
+                    for arg in &block.head_args {
+                        self.collect_declaration(&arg.raw, arg.range.start);
+                    }
+                    for item in &block.items {
+                        if let DirectiveItemSyntax::Field(field) = item
+                            && field.name == "also-as"
+                        {
+                            self.collect_declaration(&field.value, field.value_range.start);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_declaration(&mut self, text: &str, start: usize) {
+        let literals = match parse_declaration_value(text, start).value {
+            Some(DeclarationValueSyntax::Scalar(literal)) => vec![literal],
+            Some(DeclarationValueSyntax::List { items, .. }) => items,
+            None => Vec::new(),
+        };
+        for literal in literals {
+            if literal.value == self.old {
+                self.declared = true;
+                self.edits.push((literal.range, self.new.to_string()));
+            }
+        }
+    }
+
+    fn collect_body(&mut self, body: &BodySyntax, modes: &HashMap<TextRange, ResolvedBodyMode>) {
+        let macros = match modes.get(&body.range) {
+            Some(ResolvedBodyMode::TextMacro) => body
+                .parts
+                .iter()
+                .filter_map(|part| match part {
+                    BodyPartSyntax::InlineMacro(marker) => Some(marker.clone()),
+                    BodyPartSyntax::Text { .. } => None,
+                })
+                .collect(),
+            Some(ResolvedBodyMode::TypstMacro) => {
+                scan_typst_overlay_macros(&body.source, body.range).macros
+            }
+            _ => Vec::new(),
+        };
+        for marker in &macros {
+            self.collect_macro(marker);
+        }
+    }
+
+    fn collect_macro(&mut self, marker: &InlineMacroSyntax) {
+        if let [subject, _] = marker.args.as_slice() {
+            match &subject.value {
+                MacroValueSyntax::Bare(value) if value == self.old => {
+                    self.edits.push((subject.range, self.new.to_string()));
+                    return;
+                }
+                MacroValueSyntax::Quoted { value, quote } if value == self.old => {
+                    let quote = match quote {
+                        QuoteKind::Single => '\'',
+                        QuoteKind::Double => '"',
+                    };
+                    let text = format!("{quote}{}{quote}", self.new);
+                    self.edits.push((subject.range, text));
+                    return;
+                }
+                _ => {}
+            }
+        }
+        for arg in &marker.args {
+            if let MacroValueSyntax::Bare(value) = &arg.value
+                && value
+                    .strip_prefix(self.old)
+                    .is_some_and(|rest| rest.starts_with('/'))
+            {
+                let range = TextRange::new(arg.range.start, arg.range.start + self.old.len());
+                self.edits.push((range, self.new.to_string()));
+            }
+        }
+    }
+}
+
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(['_', '~', '#'])
+        && !name.chars().any(|ch| {
+            ch.is_whitespace()
+                || matches!(
+                    ch,
+                    ':' | '/' | ',' | '"' | '\'' | '\\' | '[' | ']' | '(' | ')' | '|'
+                )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::{CharacterPreset, StaticPresetCatalog};
+
+    fn catalog() -> StaticPresetCatalog {
+        StaticPresetCatalog::new(vec![
+            CharacterPreset::new("ba::柚子", ["柚子"]),
+            CharacterPreset::new("ba::桃井", ["桃井"]),
+        ])
+    }
+
+    #[test]
+    fn renames_declaration_markers_and_sticker_paths() {
+        let source = "@actor 部长\n\
+                      preset: ba::柚子\n\
+                      also-as: [柚子部长, 部长大人]\n\
+                      @end\n\
+                      > 部长: 你好 [:部长/sticker/happy:]\n\
+                      > 部长大人: 部长也在\n\
+                      > _0: 部长";
+
+        assert_eq!(
+            rename_actor(source, "部长", "会长", &catalog()).unwrap(),
+            "@actor 会长\n\
+             preset: ba::柚子\n\
+             also-as: [柚子部长, 部长大人]\n\
+             @end\n\
+             > 会长: 你好 [:会长/sticker/happy:]\n\
+             > 部长大人: 部长也在\n\
+             > _0: 部长"
+        );
+    }
+
+    #[test]
+    fn renames_marker_subjects_in_bare_and_quoted_spelling() {
+        let source = "@actor 部长\n\
+                      preset: ba::柚子\n\
+                      @end\n\
+                      - 旁白 [:部长, #1:] [:\"部长\", happy:]\n\
+                      > 部长: hi [:部长/avatar/x:] [:柚子, 部长:]";
+
+        assert_eq!(
+            rename_actor(source, "部长", "会长", &catalog()).unwrap(),
+            "@actor 会长\n\
+             preset: ba::柚子\n\
+             @end\n\
+             - 旁白 [:会长, #1:] [:\"会长\", happy:]\n\
+             > 会长: hi [:会长/avatar/x:] [:柚子, 部长:]"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_undeclared_and_taken_names() {
+        let source = "@actor 部长\npreset: ba::柚子\n@end\n> 部长: 你好";
+
+        assert_eq!(
+            rename_actor(source, "部长", "_2", &catalog()),
+            Err(RenameError::InvalidName("_2".to_string()))
+        );
+        assert_eq!(
+            rename_actor(source, "柚子", "新名字", &catalog()),
+            Err(RenameError::UndeclaredName("柚子".to_string()))
+        );
+        assert_eq!(
+            rename_actor(source, "部长", "桃井", &catalog()),
+            Err(RenameError::NameInUse("桃井".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_renames_that_rebind_references() {
+        let source = "> 柚子: 先出场\n@actor 柚子\ndisplay-name: 部长\n@end";
+
+        assert_eq!(
+            rename_actor(source, "柚子", "会长", &catalog()),
+            Err(RenameError::ChangesMeaning)
+        );
+    }
+}
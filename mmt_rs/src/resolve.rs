@@ -1,5 +1,9 @@
 //! Deterministic resource resolution between semantic lowering and materialization.
 
+use std::collections::HashSet;
+
+use serde::Serialize;
+
 use crate::diag::{Diagnostic, DiagnosticPhase, Severity};
 use crate::pack::{PackRegistry, ResolveError, StorageEntry};
 use crate::semantic::{
@@ -67,6 +71,43 @@ pub struct PackStorageSource {
     pub frame: Option<u32>,
 }
 
+impl PackStorageSource {
+    /// Pack-relative path of the file holding this resource: the item path
+    /// under the storage base for `image-dir`, or the container for
+    /// `image-sequence`.
+    pub fn file_path(&self) -> Option<String> {
+        match self.storage.kind.as_str() {
+            "image-dir" => {
+                let path = self.path.as_deref()?;
+                Some(match self.storage.base.as_deref() {
+                    Some(base) if !base.is_empty() => {
+                        format!("{}/{path}", base.trim_end_matches('/'))
+                    }
+                    _ => path.to_string(),
+                })
+            }
+            "image-sequence" => self.storage.path.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// A file a render reads, deduplicated across every resource that uses it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RequiredFile {
+    pub source: RequiredFileSource,
+    /// Range of the earliest resource in the source that uses this file.
+    pub first_range: TextRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RequiredFileSource {
+    Pack { namespace: String, path: String },
+    Workspace { path: String },
+    Remote { url: String },
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ResourceResolution {
     pub resources: Vec<ResolvedResource>,
@@ -166,6 +207,50 @@ pub fn resolve_actor_avatars(
     }
 }
 
+/// Lists the files behind resolved resources in source order. Frames of one
+/// image sequence share its container file; temporary resources are supplied
+/// by the host and are not listed.
+pub fn required_files(resolution: &ResourceResolution) -> Vec<RequiredFile> {
+    let mut resources = resolution.resources.iter().collect::<Vec<_>>();
+    resources.sort_by_key(|resource| resource.range.start);
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    for resource in resources {
+        let source = match &resource.kind {
+            ResolvedResourceKind::Sticker { source, .. }
+            | ResolvedResourceKind::Avatar { source, .. }
+            | ResolvedResourceKind::PackAsset { source, .. } => {
+                source.file_path().map(|path| RequiredFileSource::Pack {
+                    namespace: source.pack_namespace.clone(),
+                    path,
+                })
+            }
+            ResolvedResourceKind::ScriptAsset { source, .. } => Some(match source {
+                AssetSource::LocalFile(path) => {
+                    RequiredFileSource::Workspace { path: path.clone() }
+                }
+                AssetSource::Url(url) => RequiredFileSource::Remote { url: url.clone() },
+            }),
+            ResolvedResourceKind::WorkspaceFile { path } => {
+                Some(RequiredFileSource::Workspace { path: path.clone() })
+            }
+            ResolvedResourceKind::RemoteUrl { url } => {
+                Some(RequiredFileSource::Remote { url: url.clone() })
+            }
+            ResolvedResourceKind::Temporary { .. } => None,
+        };
+        if let Some(source) = source
+            && seen.insert(source.clone())
+        {
+            files.push(RequiredFile {
+                source,
+                first_range: resource.range,
+            });
+        }
+    }
+    files
+}
+
 fn resolve_avatar_selector(
     selector: &str,
     current_entity: &str,
@@ -469,4 +554,45 @@ mod tests {
             } if path == "portrait.png"
         ));
     }
+
+    #[test]
+    fn required_files_deduplicate_sequence_frames_in_source_order() {
+        let source = "> 柚子: [:#1:] [:#1:] [:asset::logo:]";
+        let (markers, actors, assets, packs) = lower(source);
+        let mut resolution = resolve_resources(&markers, &actors, &assets, &packs);
+        resolution
+            .resources
+            .extend(resolve_actor_avatars(&actors, &assets, &packs).resources);
+
+        let files = required_files(&resolution);
+        assert_eq!(
+            files
+                .iter()
+                .map(|file| (&file.source, file.first_range.start))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    &RequiredFileSource::Pack {
+                        namespace: "ba".to_string(),
+                        path: "avatars/default.png".to_string()
+                    },
+                    source.find("柚子").unwrap()
+                ),
+                (
+                    &RequiredFileSource::Pack {
+                        namespace: "ba".to_string(),
+                        path: "stickers.avifs".to_string()
+                    },
+                    source.find("[:#1:]").unwrap()
+                ),
+                (
+                    &RequiredFileSource::Pack {
+                        namespace: "ba".to_string(),
+                        path: "images/logo.png".to_string()
+                    },
+                    source.find("[:asset").unwrap()
+                ),
+            ]
+        );
+    }
 }
@@ -16,6 +16,28 @@ pub struct CharacterPreset {
     pub avatar: Option<String>,
 }
 
+impl CharacterPreset {
+    /// A preset with no display name or avatar of its own.
+    pub fn new<N: Into<String>>(id: impl Into<String>, names: impl IntoIterator<Item = N>) -> Self {
+        Self {
+            id: id.into(),
+            names: names.into_iter().map(Into::into).collect(),
+            display_name: None,
+            avatar: None,
+        }
+    }
+
+    pub fn with_display_name(mut self, display_name: impl Into<String>) -> Self {
+        self.display_name = Some(display_name.into());
+        self
+    }
+
+    pub fn with_avatar(mut self, avatar: impl Into<String>) -> Self {
+        self.avatar = Some(avatar.into());
+        self
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PresetLookup {
     Found(CharacterPreset),
@@ -66,6 +88,8 @@ pub struct ActorId(pub u32);
 pub struct ActorState {
     pub display_name: String,
     pub avatar: Option<String>,
+    /// Short name for compact renderer UI; message bubbles use `display_name`.
+    pub nickname: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -87,7 +111,14 @@ pub struct ScriptActor {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BuiltinSpeakerId(pub String);
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl BuiltinSpeakerId {
+    /// The id without its `__` prefix, as shown in reports and outlines.
+    pub fn display_name(&self) -> &str {
+        self.0.trim_start_matches('_')
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SpeakerIdentity {
     Actor(ActorId),
     Builtin(BuiltinSpeakerId),
@@ -98,6 +129,9 @@ pub struct ResolvedStatementSpeaker {
     pub statement_range: TextRange,
     pub speaker: SpeakerIdentity,
     pub revision: Option<u32>,
+    /// Whether this is the first statement in the script attributed to
+    /// `speaker`, regardless of side.
+    pub first_appearance: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -111,6 +145,9 @@ pub struct ActorLowering {
 pub struct ActorLoweringOptions {
     pub left_fallback: Option<BuiltinSpeakerId>,
     pub right_fallback: Option<BuiltinSpeakerId>,
+    /// Opt-in warning when an actor starts speaking from the opposite side,
+    /// the same check as the `side-flip` lint.
+    pub warn_side_flips: bool,
 }
 
 impl Default for ActorLoweringOptions {
@@ -118,6 +155,7 @@ impl Default for ActorLoweringOptions {
         Self {
             left_fallback: None,
             right_fallback: Some(BuiltinSpeakerId("__Sensei".to_string())),
+            warn_side_flips: false,
         }
     }
 }
@@ -145,6 +183,7 @@ struct ActorLowerer<'a, C> {
     default_actors: HashMap<String, ActorId>,
     right_history: SpeakerHistory,
     left_history: SpeakerHistory,
+    appeared: HashSet<SpeakerIdentity>,
     speakers: Vec<ResolvedStatementSpeaker>,
     diagnostics: Vec<Diagnostic>,
 }
@@ -161,6 +200,7 @@ struct ActorPatch {
     preset: Option<(String, TextRange)>,
     display_name: Option<(String, TextRange)>,
     avatar: Option<(String, TextRange)>,
+    nickname: Option<(String, TextRange)>,
     additional_names: Vec<(String, TextRange)>,
 }
 
@@ -174,6 +214,7 @@ impl<'a, C: CharacterPresetCatalog> ActorLowerer<'a, C> {
             default_actors: HashMap::new(),
             right_history: SpeakerHistory::default(),
             left_history: SpeakerHistory::default(),
+            appeared: HashSet::new(),
             speakers: Vec::new(),
             diagnostics: Vec::new(),
         }
@@ -185,10 +226,20 @@ impl<'a, C: CharacterPresetCatalog> ActorLowerer<'a, C> {
                 SyntaxNode::DirectiveBlock(block) if block.name == "actor" => {
                     self.lower_actor_block(block);
                 }
+                SyntaxNode::DirectiveLine(line) if line.name == "actor" => {
+                    self.error(
+                        "@actor requires an aggregated block ending with @end",
+                        line.range,
+                    );
+                }
                 SyntaxNode::Statement(statement) => self.lower_statement(statement),
                 _ => {}
             }
         }
+        if self.options.warn_side_flips {
+            let flips = crate::lint::side_flips(document, &self.actors, &self.speakers);
+            self.diagnostics.extend(flips);
+        }
         ActorLowering {
             actors: self.actors,
             speakers: self.speakers,
@@ -304,6 +355,7 @@ impl<'a, C: CharacterPresetCatalog> ActorLowerer<'a, C> {
                 "preset" => patch.preset = self.parse_scalar_field(field),
                 "display-name" => patch.display_name = self.parse_scalar_field(field),
                 "avatar" => patch.avatar = self.parse_scalar_field(field),
+                "nickname" => patch.nickname = self.parse_scalar_field(field),
                 "also-as" => patch.additional_names = self.parse_name_list(field),
                 _ => self.error(
                     format!("unknown @actor field '{}'", field.name),
@@ -400,6 +452,7 @@ impl<'a, C: CharacterPresetCatalog> ActorLowerer<'a, C> {
                 .as_ref()
                 .map(|(value, _)| value.clone())
                 .or_else(|| preset.avatar.clone()),
+            nickname: patch.nickname.as_ref().map(|(value, _)| value.clone()),
         };
         for name in &names {
             self.names.insert(name.clone(), id);
@@ -450,7 +503,7 @@ impl<'a, C: CharacterPresetCatalog> ActorLowerer<'a, C> {
             }
         }
 
-        if patch.display_name.is_none() && patch.avatar.is_none() {
+        if patch.display_name.is_none() && patch.avatar.is_none() && patch.nickname.is_none() {
             return;
         }
         let actor = &mut self.actors[actor_id.0 as usize];
@@ -466,6 +519,9 @@ impl<'a, C: CharacterPresetCatalog> ActorLowerer<'a, C> {
         if let Some((avatar, _)) = &patch.avatar {
             state.avatar = Some(avatar.clone());
         }
+        if let Some((nickname, _)) = &patch.nickname {
+            state.nickname = Some(nickname.clone());
+        }
         actor.revisions.push(ActorRevision {
             number: actor.revisions.len() as u32,
             state,
@@ -534,11 +590,7 @@ impl<'a, C: CharacterPresetCatalog> ActorLowerer<'a, C> {
         if let Some(actor_id) = self.history(statement.kind).current {
             self.capture_actor_speaker(statement, actor_id);
         } else if let Some(builtin) = self.fallback_speaker(statement.kind).cloned() {
-            self.speakers.push(ResolvedStatementSpeaker {
-                statement_range: statement.range,
-                speaker: SpeakerIdentity::Builtin(builtin),
-                revision: None,
-            });
+            self.push_speaker(statement, SpeakerIdentity::Builtin(builtin), None);
         } else {
             self.error(
                 format!(
@@ -556,12 +608,23 @@ impl<'a, C: CharacterPresetCatalog> ActorLowerer<'a, C> {
             .last()
             .expect("actors always have an initial revision")
             .number;
+        self.push_speaker(statement, SpeakerIdentity::Actor(actor_id), Some(revision));
+        self.history_mut(statement.kind).record(actor_id);
+    }
+
+    fn push_speaker(
+        &mut self,
+        statement: &StatementSyntax,
+        speaker: SpeakerIdentity,
+        revision: Option<u32>,
+    ) {
+        let first_appearance = self.appeared.insert(speaker.clone());
         self.speakers.push(ResolvedStatementSpeaker {
             statement_range: statement.range,
-            speaker: SpeakerIdentity::Actor(actor_id),
-            revision: Some(revision),
+            speaker,
+            revision,
+            first_appearance,
         });
-        self.history_mut(statement.kind).record(actor_id);
     }
 
     fn resolve_explicit_speaker(&mut self, name: &str, range: TextRange) -> Option<ActorId> {
@@ -696,7 +759,7 @@ fn deduplicate_names(names: Vec<String>) -> Vec<String> {
         .collect()
 }
 
-fn side_name(kind: StatementKind) -> &'static str {
+pub(crate) fn side_name(kind: StatementKind) -> &'static str {
     match kind {
         StatementKind::Right => "right-side",
         StatementKind::Left => "left-side",
@@ -710,12 +773,7 @@ mod tests {
     use crate::parse_text;
 
     fn preset(id: &str, names: &[&str]) -> CharacterPreset {
-        CharacterPreset {
-            id: id.to_string(),
-            names: names.iter().map(|name| (*name).to_string()).collect(),
-            display_name: None,
-            avatar: Some(format!("{id}/avatar/default")),
-        }
+        CharacterPreset::new(id, names.iter().copied()).with_avatar(format!("{id}/avatar/default"))
     }
 
     fn catalog() -> StaticPresetCatalog {
@@ -728,6 +786,54 @@ mod tests {
         ])
     }
 
+    #[test]
+    fn statement_avatar_patch_does_not_create_actor_revision() {
+        let document = parse_text(
+            "> 日富美: 初次\n\
+             @actor 日富美\n\
+             avatar: ba::日富美/avatar/smile\n\
+             @end\n\
+             >(avatar: \"beach.png\") 日富美: 换装\n\
+             > 日富美: 换回来",
+        );
+        assert!(document.diagnostics.is_empty());
+
+        let lowered = lower_actors(&document, &catalog());
+        assert!(lowered.diagnostics.is_empty());
+        assert_eq!(lowered.actors[0].revisions.len(), 2);
+        assert_eq!(
+            lowered
+                .speakers
+                .iter()
+                .map(|speaker| speaker.revision)
+                .collect::<Vec<_>>(),
+            vec![Some(0), Some(1), Some(1)]
+        );
+    }
+
+    #[test]
+    fn nickname_is_revision_state_separate_from_display_name() {
+        let document = parse_text(
+            "@actor 日富美\n\
+             preset: ba::日富美\n\
+             display-name: \"小鸟游日富美\"\n\
+             @end\n\
+             > 日富美: first\n\
+             @actor 日富美\n\
+             nickname: 日富美酱\n\
+             @end\n\
+             > 日富美: second",
+        );
+        let lowered = lower_actors(&document, &catalog());
+
+        assert!(lowered.diagnostics.is_empty(), "{:?}", lowered.diagnostics);
+        let revisions = &lowered.actors[0].revisions;
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].state.nickname, None);
+        assert_eq!(revisions[1].state.nickname.as_deref(), Some("日富美酱"));
+        assert_eq!(revisions[1].state.display_name, "小鸟游日富美");
+    }
+
     #[test]
     fn lazy_actor_aliases_and_revisions_share_one_identity() {
         let document = parse_text(
@@ -849,6 +955,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn escaped_speaker_markers_resolve_reference_like_pack_names() {
+        let catalog = StaticPresetCatalog::new(vec![
+            preset("custom::_2", &["_2"]),
+            preset("custom::~chan", &["~chan"]),
+        ]);
+        let document = parse_text(
+            "> \\_2: escaped\n\
+             > \"~chan\": quoted\n\
+             > ~1: first unique speaker\n\
+             > _3: out of range",
+        );
+        let lowered = lower_actors(&document, &catalog);
+
+        assert_eq!(lowered.diagnostics.len(), 1);
+        assert!(
+            lowered.diagnostics[0]
+                .message
+                .contains("invalid left-side speaker reference _3")
+        );
+        assert_eq!(lowered.actors.len(), 2);
+        assert_eq!(lowered.actors[0].preset_id, "custom::_2");
+        assert_eq!(
+            lowered
+                .speakers
+                .iter()
+                .map(|speaker| speaker.speaker.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                SpeakerIdentity::Actor(ActorId(0)),
+                SpeakerIdentity::Actor(ActorId(1)),
+                SpeakerIdentity::Actor(ActorId(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_actor_names_and_display_names_may_contain_spaces() {
+        let document = parse_text(
+            "@actor \"Hanaoka Yuzu\"\n\
+             preset: ba::柚子\n\
+             display-name: \"  柚子 (游戏部)  \"\n\
+             also-as: [\"Game Dev Yuzu\"]\n\
+             @end\n\
+             > Hanaoka Yuzu: one\n\
+             > \"Game Dev Yuzu\": two",
+        );
+        let lowered = lower_actors(&document, &catalog());
+
+        assert!(lowered.diagnostics.is_empty(), "{:?}", lowered.diagnostics);
+        assert_eq!(lowered.actors.len(), 1);
+        assert_eq!(
+            lowered.actors[0].names,
+            vec!["Hanaoka Yuzu", "Game Dev Yuzu"]
+        );
+        assert_eq!(
+            lowered.actors[0].revisions[0].state.display_name,
+            "  柚子 (游戏部)  "
+        );
+        assert_eq!(lowered.speakers.len(), 2);
+        assert!(
+            lowered
+                .speakers
+                .iter()
+                .all(|speaker| speaker.speaker == SpeakerIdentity::Actor(ActorId(0)))
+        );
+    }
+
     #[test]
     fn backref_one_alternates_between_recent_distinct_speakers() {
         let document = parse_text(
@@ -961,6 +1135,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn colon_form_actor_directive_is_rejected() {
+        let document = parse_text("@actor: 日富美\n> 日富美: hi");
+        let lowered = lower_actors(&document, &catalog());
+
+        assert_eq!(lowered.diagnostics.len(), 1);
+        assert!(
+            lowered.diagnostics[0]
+                .message
+                .contains("@actor requires an aggregated block")
+        );
+        assert_eq!(lowered.actors.len(), 1);
+    }
+
     #[test]
     fn ambiguous_catalog_names_do_not_lazily_create_an_actor() {
         let catalog =
@@ -1005,11 +1193,58 @@ mod tests {
         assert_eq!(lowered.speakers[3].revision, None);
     }
 
+    #[test]
+    fn first_appearance_is_tracked_per_speaker_across_sides() {
+        let document = parse_text("> 柚子: 你好\n< 早\n> 再见\n< 柚子: 换边\n> 日富美: 我来了");
+        let lowered = lower_actors(&document, &catalog());
+
+        assert!(lowered.diagnostics.is_empty(), "{:?}", lowered.diagnostics);
+        assert_eq!(
+            lowered
+                .speakers
+                .iter()
+                .map(|speaker| speaker.first_appearance)
+                .collect::<Vec<_>>(),
+            vec![true, true, false, false, true]
+        );
+    }
+
+    #[test]
+    fn side_flip_lint_warns_once_per_switch_when_enabled() {
+        let source = "> 柚子: 你好\n> 再见\n< 柚子: 换边了\n< 还在右边\n> 柚子: 回来了";
+        let document = parse_text(source);
+        let options = ActorLoweringOptions {
+            warn_side_flips: true,
+            ..ActorLoweringOptions::default()
+        };
+
+        assert!(lower_actors(&document, &catalog()).diagnostics.is_empty());
+        let lowered = lower_actors_with_options(&document, &catalog(), &options);
+
+        assert_eq!(lowered.diagnostics.len(), 2, "{:?}", lowered.diagnostics);
+        assert!(
+            lowered
+                .diagnostics
+                .iter()
+                .all(|diagnostic| diagnostic.severity == Severity::Warning)
+        );
+        assert_eq!(
+            lowered.diagnostics[0].message,
+            "actor '柚子' switches to right-side dialogue after speaking as left-side dialogue"
+        );
+        assert_eq!(
+            lowered.diagnostics[0].range.map(|range| range.start),
+            source.find("< 柚子")
+        );
+        assert_eq!(lowered.diagnostics[0].labels[0].range.start, 0);
+    }
+
     #[test]
     fn fallback_builtin_speakers_are_configurable_by_side() {
         let options = ActorLoweringOptions {
             left_fallback: Some(BuiltinSpeakerId("narrator-left".to_string())),
             right_fallback: Some(BuiltinSpeakerId("narrator-right".to_string())),
+            ..ActorLoweringOptions::default()
         };
         let document = parse_text("> left fallback\n< right fallback");
         let lowered = lower_actors_with_options(&document, &catalog(), &options);
@@ -159,7 +159,11 @@ impl AssetLowerer {
                 self.apply_short_field(&mut draft, key, raw, range);
             } else if draft.source.is_none() && index == 1 {
                 draft.source = self.parse_scalar(&token.raw, token.range, "asset source");
-            } else if let Some((key, _)) = token.raw.split_once(':') {
+            } else if let Some((key, _)) = token
+                .raw
+                .split_once(':')
+                .filter(|(_, value)| !value.starts_with("//"))
+            {
                 self.error(format!("unknown @asset field '{key}'"), token.range);
             } else {
                 self.error("unexpected @asset short-form argument", token.range);
@@ -439,6 +443,19 @@ mod tests {
         assert_eq!(lowered.diagnostics.len(), 3);
     }
 
+    #[test]
+    fn misplaced_short_form_url_is_not_reported_as_a_field() {
+        let document = parse_text("@asset: hero ns:project https://example.com/a.png");
+        let lowered = lower_assets(&document);
+
+        assert!(lowered.assets.is_empty());
+        assert_eq!(lowered.diagnostics.len(), 1);
+        assert_eq!(
+            lowered.diagnostics[0].message,
+            "unexpected @asset short-form argument"
+        );
+    }
+
     #[test]
     fn local_asset_sources_reject_paths_and_traversal() {
         let document = parse_text(
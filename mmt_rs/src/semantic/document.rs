@@ -184,6 +184,12 @@ impl DocumentLowerer {
                 SyntaxNode::DirectiveBlock(block) if block.name == "typ" => {
                     self.seen_renderable = true;
                 }
+                SyntaxNode::DirectiveLine(line) if !is_known_directive(&line.name) => {
+                    self.report_unknown_directive(&line.name, line.name_range);
+                }
+                SyntaxNode::DirectiveBlock(block) if !is_known_directive(&block.name) => {
+                    self.report_unknown_directive(&block.name, block.name_range);
+                }
                 _ => {}
             }
         }
@@ -193,6 +199,13 @@ impl DocumentLowerer {
         }
     }
 
+    fn report_unknown_directive(&mut self, name: &str, range: TextRange) {
+        self.diagnostics.push(semantic_error(
+            format!("unknown directive '@{name}'"),
+            range,
+        ));
+    }
+
     fn lower_block(&mut self, block: &DirectiveBlockSyntax) {
         if let Some(first) = self.first_document {
             self.diagnostics.push(
@@ -444,6 +457,15 @@ fn validate_offset_minutes(minutes: i16) -> Result<(), String> {
         .map_err(|_| "timezone offset is outside the supported range".to_string())
 }
 
+/// Directives claimed by the parser or a lowering pass; anything else would
+/// otherwise be dropped silently by the emitter.
+fn is_known_directive(name: &str) -> bool {
+    matches!(
+        name,
+        "actor" | "asset" | "bond" | "comment" | "document" | "mode" | "reply" | "typ"
+    )
+}
+
 fn semantic_error(message: impl Into<String>, range: TextRange) -> Diagnostic {
     Diagnostic::new(
         Severity::Error,
@@ -597,6 +619,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reports_unknown_directives_instead_of_dropping_them() {
+        let document = parse_text(
+            "@title: 旧标题\n\
+             @meta\n\
+             cover: https://example.com/a.png\n\
+             @end\n\
+             @mode: t\n\
+             @typ: #let x = 1",
+        );
+        let lowered = lower_document(&document);
+
+        let messages = lowered
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec!["unknown directive '@title'", "unknown directive '@meta'"]
+        );
+        assert_eq!(lowered.diagnostics[0].range, Some(TextRange::new(1, 6)));
+    }
+
     #[test]
     fn rejects_invalid_auto_format_and_timezone() {
         let document = parse_text(
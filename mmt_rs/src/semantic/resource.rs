@@ -561,12 +561,7 @@ mod tests {
 
     fn lower(source: &str) -> ResourceLowering {
         let document = parse_text(source);
-        let catalog = StaticPresetCatalog::new(vec![CharacterPreset {
-            id: "ba::柚子".to_string(),
-            names: vec!["柚子".to_string()],
-            display_name: None,
-            avatar: None,
-        }]);
+        let catalog = StaticPresetCatalog::new(vec![CharacterPreset::new("ba::柚子", ["柚子"])]);
         let modes = resolve_body_modes(&document);
         let actors = lower_actors(&document, &catalog);
         assert!(document.diagnostics.is_empty());
@@ -0,0 +1,111 @@
+//! Source-preserving correction of statements written on the wrong side.
+
+use std::collections::HashMap;
+
+use crate::semantic::{ActorId, CharacterPresetCatalog, SpeakerIdentity, lower_actors};
+use crate::source::TextRange;
+use crate::syntax::{StatementKind, SyntaxDocument, SyntaxNode};
+
+/// Moves statements back to the side each actor first spoke from with a
+/// speaker marker, by swapping `>` and `<`. A moved statement takes along
+/// the bare statements after it on the same side that it spoke for.
+///
+/// Each correction is lowered again and kept only if every statement still
+/// resolves to the same speaker. A statement that cannot move without
+/// rebinding a neighbouring bare line is left as written, for the
+/// `side-flip` lint to report.
+pub fn fix_side_flips(source: &str, catalog: &impl CharacterPresetCatalog) -> String {
+    let document = crate::parse_text(source);
+    let expected = statement_speakers(&document, catalog);
+    let speakers = expected.iter().cloned().collect::<HashMap<_, _>>();
+    let mut first_sides = HashMap::new();
+    let mut open_runs: HashMap<StatementKind, (ActorId, usize)> = HashMap::new();
+    let mut runs: Vec<Vec<usize>> = Vec::new();
+    for node in &document.nodes {
+        let SyntaxNode::Statement(statement) = node else {
+            continue;
+        };
+        if statement.kind == StatementKind::Narration {
+            continue;
+        }
+        let actor = match speakers.get(&statement.range) {
+            Some(SpeakerIdentity::Actor(actor_id)) => Some(*actor_id),
+            _ => None,
+        };
+        if statement.marker.is_none() {
+            if let (Some(actor_id), Some(&(run_actor, index))) =
+                (actor, open_runs.get(&statement.kind))
+                && run_actor == actor_id
+            {
+                runs[index].push(statement.range.start);
+            }
+            continue;
+        }
+        open_runs.remove(&statement.kind);
+        let Some(actor_id) = actor else {
+            continue;
+        };
+        if *first_sides.entry(actor_id).or_insert(statement.kind) != statement.kind {
+            open_runs.insert(statement.kind, (actor_id, runs.len()));
+            runs.push(vec![statement.range.start]);
+        }
+    }
+
+    let mut fixed = source.to_string();
+    for run in runs {
+        let mut candidate = fixed.clone();
+        for offset in run {
+            let swapped = match candidate.as_bytes()[offset] {
+                b'>' => "<",
+                b'<' => ">",
+                _ => continue,
+            };
+            candidate.replace_range(offset..offset + 1, swapped);
+        }
+        if statement_speakers(&crate::parse_text(&candidate), catalog) == expected {
+            fixed = candidate;
+        }
+    }
+    fixed
+}
+
+fn statement_speakers(
+    document: &SyntaxDocument,
+    catalog: &impl CharacterPresetCatalog,
+) -> Vec<(TextRange, SpeakerIdentity)> {
+    lower_actors(document, catalog)
+        .speakers
+        .into_iter()
+        .map(|speaker| (speaker.statement_range, speaker.speaker))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::{CharacterPreset, StaticPresetCatalog};
+
+    fn catalog() -> StaticPresetCatalog {
+        StaticPresetCatalog::new(vec![
+            CharacterPreset::new("test::A", ["A"]),
+            CharacterPreset::new("test::B", ["B"]),
+        ])
+    }
+
+    #[test]
+    fn moves_wrong_side_statements_with_their_bare_follow_ups() {
+        let source = "> A: 一\n< B: 二\n<(fill: red) A: 三\n< 四\n- 旁白\n> 五\n< B: 六";
+
+        assert_eq!(
+            fix_side_flips(source, &catalog()),
+            "> A: 一\n< B: 二\n>(fill: red) A: 三\n> 四\n- 旁白\n> 五\n< B: 六"
+        );
+    }
+
+    #[test]
+    fn keeps_statements_whose_move_would_rebind_a_bare_line() {
+        let source = "> A: 一\n> B: 二\n< A: 三\n> 四";
+
+        assert_eq!(fix_side_flips(source, &catalog()), source);
+    }
+}
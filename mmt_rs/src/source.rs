@@ -109,6 +109,19 @@ impl SourceFile {
             .map(|(offset, _)| line_start + offset)
             .or_else(|| (position.column == line.chars().count() + 1).then_some(line_end))
     }
+
+    /// Returns the text of a 1-based line without its line terminator.
+    pub fn line_text(&self, line: usize) -> Option<&str> {
+        let line_start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let line_end = self
+            .line_starts
+            .get(line)
+            .copied()
+            .unwrap_or(self.text.len());
+        let text = &self.text[line_start..line_end];
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        Some(text.strip_suffix('\r').unwrap_or(text))
+    }
 }
 
 #[cfg(test)]
@@ -133,6 +146,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn line_text_excludes_line_terminators() {
+        let source = SourceFile::anonymous("一二\r\nabc\n");
+
+        assert_eq!(source.line_text(0), None);
+        assert_eq!(source.line_text(1), Some("一二"));
+        assert_eq!(source.line_text(2), Some("abc"));
+        assert_eq!(source.line_text(3), Some(""));
+        assert_eq!(source.line_text(4), None);
+    }
+
     #[test]
     fn rejects_offsets_inside_utf8_codepoints() {
         let source = SourceFile::anonymous("晴");
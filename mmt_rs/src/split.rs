@@ -0,0 +1,274 @@
+//! Source-preserving split of long text bubbles into one bubble per sentence.
+
+use std::collections::HashMap;
+use std::mem::{Discriminant, discriminant};
+
+use crate::semantic::{
+    CharacterPresetCatalog, ResolvedBodyMode, SpeakerIdentity, lower_actors, resolve_body_modes,
+};
+use crate::source::TextRange;
+use crate::syntax::{BodyPartSyntax, StatementKind, StatementSyntax, SyntaxNode};
+
+const OPENERS: &[char] = &['「', '『', '（', '(', '“', '‘', '《', '【', '['];
+const CLOSERS: &[char] = &['」', '』', '）', ')', '”', '’', '》', '】', ']'];
+
+/// Splits text-mode statement bodies after sentence terminators so every
+/// sentence becomes its own statement. Later sentences are written as bare
+/// markers on the same side, so they keep the original speaker; a fenced
+/// body is closed and reopened with the same fence around each sentence.
+/// A dialogue sentence that would read as a speaker marker or patch gets
+/// the empty `: ` marker, and such a narration sentence is fenced.
+///
+/// CJK terminators (`。！？…`) split anywhere, Latin ones (`.!?`) only before
+/// whitespace. Nothing splits inside brackets or quotes, inside inline macros,
+/// in Typst bodies, or in statements carrying a patch. Each split is lowered
+/// again and kept only if the new statements parse as plain statements of the
+/// same speaker.
+pub fn split_sentences(source: &str, catalog: &impl CharacterPresetCatalog) -> String {
+    let document = crate::parse_text(source);
+    let modes = resolve_body_modes(&document)
+        .bodies
+        .into_iter()
+        .map(|entry| (entry.range, entry.mode))
+        .collect::<HashMap<_, _>>();
+    let mut candidates = Vec::new();
+    for (index, node) in document.nodes.iter().enumerate() {
+        let SyntaxNode::Statement(statement) = node else {
+            continue;
+        };
+        if statement.patch.is_some()
+            || !matches!(
+                modes.get(&statement.body.range),
+                Some(ResolvedBodyMode::TextMacro | ResolvedBodyMode::TextRaw)
+            )
+        {
+            continue;
+        }
+        let separators = sentence_separators(source, statement);
+        if separators.is_empty() {
+            continue;
+        }
+        let (open, close) = fence(source, statement);
+        let prefix = match statement.kind {
+            StatementKind::Left => ">",
+            StatementKind::Right => "<",
+            StatementKind::Narration => "-",
+        };
+        let mut edits = Vec::new();
+        for (sentence, separator) in separators.iter().enumerate() {
+            let end = separators
+                .get(sentence + 1)
+                .map_or(statement.body.range.end, |next| next.start);
+            let text = &source[separator.end..end];
+            let guarded = reads_as_marker_or_patch(&format!("{prefix} {open}{text}"));
+            let (lead, fenced) = match statement.kind {
+                StatementKind::Narration if guarded && open.is_empty() => ("\"\"\"", true),
+                StatementKind::Left | StatementKind::Right if guarded => (": ", false),
+                _ => ("", false),
+            };
+            edits.push((*separator, format!("{close}\n{prefix} {lead}{open}")));
+            if fenced {
+                edits.push((TextRange::empty(end), "\"\"\"".to_string()));
+            }
+        }
+        candidates.push((index, separators.len() + 1, edits));
+    }
+
+    let mut split = source.to_string();
+    for (index, count, edits) in candidates.into_iter().rev() {
+        let mut candidate = split.clone();
+        for (range, text) in edits.into_iter().rev() {
+            candidate.replace_range(range.start..range.end, &text);
+        }
+        let mut expected = node_shapes(&split, catalog);
+        let statement = expected[index].clone();
+        expected.splice(index..=index, std::iter::repeat_n(statement, count));
+        if node_shapes(&candidate, catalog) == expected {
+            split = candidate;
+        }
+    }
+    split
+}
+
+/// Whether a statement line starts with a speaker marker, history reference
+/// or patch, which a sentence moved behind a bare marker must not do.
+fn reads_as_marker_or_patch(line: &str) -> bool {
+    match crate::parse_text(line).nodes.first() {
+        Some(SyntaxNode::Statement(statement)) => {
+            statement.marker.is_some() || statement.patch.is_some()
+        }
+        _ => true,
+    }
+}
+
+/// Node kind, statement side, patch presence and resolved speaker of every
+/// node, for checking that a split only duplicated one statement.
+fn node_shapes(
+    source: &str,
+    catalog: &impl CharacterPresetCatalog,
+) -> Vec<(Discriminant<SyntaxNode>, Option<StatementShape>)> {
+    let document = crate::parse_text(source);
+    let speakers = lower_actors(&document, catalog)
+        .speakers
+        .into_iter()
+        .map(|speaker| (speaker.statement_range, speaker.speaker))
+        .collect::<HashMap<_, _>>();
+    document
+        .nodes
+        .iter()
+        .map(|node| {
+            let statement = match node {
+                SyntaxNode::Statement(statement) => Some((
+                    statement.kind,
+                    statement.patch.is_some(),
+                    speakers.get(&statement.range).cloned(),
+                )),
+                _ => None,
+            };
+            (discriminant(node), statement)
+        })
+        .collect()
+}
+
+type StatementShape = (StatementKind, bool, Option<SpeakerIdentity>);
+
+/// Ranges between sentences: from the end of a terminator run to the start
+/// of the next sentence, skipping the whitespace in between.
+fn sentence_separators(source: &str, statement: &StatementSyntax) -> Vec<TextRange> {
+    let body_end = statement.body.range.end;
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut separators = Vec::new();
+    for part in &statement.body.parts {
+        let BodyPartSyntax::Text { range, .. } = part else {
+            continue;
+        };
+        let text = &source[range.start..range.end];
+        let mut chars = text.char_indices().peekable();
+        while let Some((_, ch)) = chars.next() {
+            if OPENERS.contains(&ch) {
+                depth += 1;
+                continue;
+            }
+            if CLOSERS.contains(&ch) {
+                depth = depth.saturating_sub(1);
+                continue;
+            }
+            if ch == '"' {
+                quoted = !quoted;
+                continue;
+            }
+            if depth > 0 || quoted || !is_terminator(ch) {
+                continue;
+            }
+            while chars.next_if(|(_, next)| is_terminator(*next)).is_some() {}
+            let end = chars.peek().map_or(text.len(), |(offset, _)| *offset);
+            let rest = &text[end..];
+            let gap = rest.len() - rest.trim_start().len();
+            if ch.is_ascii() && gap == 0 && !rest.is_empty() {
+                continue;
+            }
+            let separator = TextRange::new(range.start + end, range.start + end + gap);
+            if source[separator.end..body_end].trim().is_empty() {
+                continue;
+            }
+            separators.push(separator);
+            while chars.next_if(|(offset, _)| *offset < end + gap).is_some() {}
+        }
+    }
+    separators
+}
+
+fn is_terminator(ch: char) -> bool {
+    matches!(ch, '。' | '！' | '？' | '…' | '.' | '!' | '?')
+}
+
+/// Opening and closing fence text of a fenced statement body, or empty
+/// strings for an unfenced one.
+fn fence<'a>(source: &'a str, statement: &StatementSyntax) -> (&'a str, &'a str) {
+    let tail = &source[statement.body.range.end..statement.range.end];
+    if !tail.starts_with('"') {
+        return ("", "");
+    }
+    let head = &source[statement.range.start..statement.body.range.start];
+    let Some(run_end) = head.rfind('"').map(|offset| offset + 1) else {
+        return ("", "");
+    };
+    let run_start = head[..run_end].trim_end_matches('"').len();
+    let open_start = ["rt", "t"]
+        .iter()
+        .find(|prefix| head[..run_start].ends_with(*prefix))
+        .map_or(run_start, |prefix| run_start - prefix.len());
+    (
+        &head[open_start..run_end],
+        &tail[..tail.len() - tail.trim_start_matches('"').len()],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::{CharacterPreset, StaticPresetCatalog};
+
+    fn split(source: &str) -> String {
+        let catalog = StaticPresetCatalog::new(vec![
+            CharacterPreset::new("test::A", ["A"]),
+            CharacterPreset::new("test::B", ["B"]),
+        ]);
+        split_sentences(source, &catalog)
+    }
+
+    #[test]
+    fn splits_unfenced_bodies_on_cjk_and_latin_terminators() {
+        assert_eq!(
+            split("> A: 你好。今天天气不错！\n< Fine. See you at 3.30?\n- 旁白"),
+            "> A: 你好。\n> 今天天气不错！\n< Fine.\n< See you at 3.30?\n- 旁白"
+        );
+    }
+
+    #[test]
+    fn keeps_brackets_quotes_and_inline_macros_whole() {
+        let source = "> A: 她说「走吧。快点！」然后走了。[:A/sticker/wave。:]好。";
+
+        assert_eq!(
+            split(source),
+            "> A: 她说「走吧。快点！」然后走了。\n> [:A/sticker/wave。:]好。"
+        );
+        assert_eq!(
+            split("> A: He said \"Stop. Now.\" and left. Don't. Ok."),
+            "> A: He said \"Stop. Now.\" and left.\n> Don't.\n> Ok."
+        );
+    }
+
+    #[test]
+    fn reopens_fences_and_skips_typst_and_patched_bodies() {
+        let source = "> A: t\"\"\"\n第一句。\n第二句。\n\"\"\"\n\
+                      > B: T\"\"\"一。二。\"\"\"\n\
+                      >(fill: blue) B: 一。二。";
+
+        assert_eq!(
+            split(source),
+            "> A: t\"\"\"\n第一句。\"\"\"\n> t\"\"\"第二句。\n\"\"\"\n\
+             > B: T\"\"\"一。二。\"\"\"\n\
+             >(fill: blue) B: 一。二。"
+        );
+    }
+
+    #[test]
+    fn guards_sentences_that_would_parse_as_markers_or_patches() {
+        assert_eq!(split("> A: 好。时间: 12点"), "> A: 好。\n> : 时间: 12点");
+        assert_eq!(split("> A: 好。_2: 不"), "> A: 好。\n> : _2: 不");
+        assert_eq!(
+            split("- 旁白。(笑)继续"),
+            "- 旁白。\n- \"\"\"(笑)继续\"\"\""
+        );
+    }
+
+    #[test]
+    fn leaves_statements_whose_split_changes_the_speaker() {
+        // The second sentence would open a fence that swallows `> B: 嗯`.
+        let source = "> A: 好。t\"\"\"x\n> B: 嗯";
+
+        assert_eq!(split(source), source);
+    }
+}
@@ -0,0 +1,277 @@
+//! Per-speaker message statistics, speaker-name resolution and actor timelines.
+
+use std::collections::HashMap;
+
+use crate::semantic::{ActorId, ActorLowering, ActorState, SpeakerIdentity};
+use crate::source::TextRange;
+use crate::syntax::{SpeakerMarkerSyntax, SyntaxDocument, SyntaxNode};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeakerStats {
+    pub speaker: SpeakerIdentity,
+    /// Actor primary name, or the built-in speaker's display name.
+    pub name: String,
+    pub messages: usize,
+    /// Non-whitespace characters across this speaker's message bodies.
+    pub characters: usize,
+    /// Most messages this speaker sent without another statement in between.
+    pub longest_run: usize,
+    /// Fraction of all spoken characters, between 0 and 1.
+    pub share: f64,
+    /// Range of the speaker's first statement.
+    pub first_range: TextRange,
+}
+
+/// Counts messages per speaker in order of first appearance. Narration and
+/// statements without a resolved speaker are not attributed to anyone.
+pub fn speaker_report(document: &SyntaxDocument, actors: &ActorLowering) -> Vec<SpeakerStats> {
+    let speakers = actors
+        .speakers
+        .iter()
+        .map(|speaker| (speaker.statement_range, &speaker.speaker))
+        .collect::<HashMap<_, _>>();
+    let mut report: Vec<SpeakerStats> = Vec::new();
+    let mut run: Option<(usize, usize)> = None;
+    for node in &document.nodes {
+        let statement = match node {
+            SyntaxNode::Statement(statement) => statement,
+            node if matches!(node, SyntaxNode::Blank(_)) || node.is_comment() => continue,
+            _ => {
+                run = None;
+                continue;
+            }
+        };
+        let Some(&speaker) = speakers.get(&statement.range) else {
+            run = None;
+            continue;
+        };
+        let index = match report.iter().position(|stats| stats.speaker == *speaker) {
+            Some(index) => index,
+            None => {
+                report.push(SpeakerStats {
+                    speaker: speaker.clone(),
+                    name: speaker_name(speaker, actors),
+                    messages: 0,
+                    characters: 0,
+                    longest_run: 0,
+                    share: 0.0,
+                    first_range: statement.range,
+                });
+                report.len() - 1
+            }
+        };
+        let length = match run {
+            Some((last, length)) if last == index => length + 1,
+            _ => 1,
+        };
+        run = Some((index, length));
+        let stats = &mut report[index];
+        stats.messages += 1;
+        stats.characters += statement
+            .body
+            .source
+            .chars()
+            .filter(|ch| !ch.is_whitespace())
+            .count();
+        stats.longest_run = stats.longest_run.max(length);
+    }
+    let total = report.iter().map(|stats| stats.characters).sum::<usize>();
+    if total > 0 {
+        for stats in &mut report {
+            stats.share = stats.characters as f64 / total as f64;
+        }
+    }
+    report
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameResolution {
+    /// The token is the actor's primary name.
+    PrimaryName,
+    /// The token is another name added through the preset or `also-as`.
+    AdditionalName,
+    /// The token did not resolve; actor lowering reported why.
+    Unresolved,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeakerToken {
+    /// Explicit speaker marker text as written.
+    pub raw: String,
+    pub actor: Option<ActorId>,
+    pub resolution: NameResolution,
+    /// Every marker range using this token for this actor, in source order.
+    pub ranges: Vec<TextRange>,
+}
+
+/// Lists each distinct explicit speaker marker with the actor it resolved
+/// to, in order of first use. A name that binds to different actors at
+/// different points in the script gets one entry per actor.
+pub fn speaker_tokens(document: &SyntaxDocument, actors: &ActorLowering) -> Vec<SpeakerToken> {
+    let speakers = actors
+        .speakers
+        .iter()
+        .map(|speaker| (speaker.statement_range, &speaker.speaker))
+        .collect::<HashMap<_, _>>();
+    let mut tokens: Vec<SpeakerToken> = Vec::new();
+    for node in &document.nodes {
+        let SyntaxNode::Statement(statement) = node else {
+            continue;
+        };
+        let Some(SpeakerMarkerSyntax::Explicit { raw, range }) = &statement.marker else {
+            continue;
+        };
+        let actor = match speakers.get(&statement.range) {
+            Some(SpeakerIdentity::Actor(actor_id)) => Some(*actor_id),
+            _ => None,
+        };
+        if let Some(token) = tokens
+            .iter_mut()
+            .find(|token| token.raw == *raw && token.actor == actor)
+        {
+            token.ranges.push(*range);
+            continue;
+        }
+        let resolution = match actor.map(|actor_id| &actors.actors[actor_id.0 as usize]) {
+            Some(actor) if actor.primary_name == *raw => NameResolution::PrimaryName,
+            Some(_) => NameResolution::AdditionalName,
+            None => NameResolution::Unresolved,
+        };
+        tokens.push(SpeakerToken {
+            raw: raw.clone(),
+            actor,
+            resolution,
+            ranges: vec![*range],
+        });
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActorEvent {
+    /// The actor was created or patched by an `@actor` block.
+    Revision {
+        number: u32,
+        state: ActorState,
+        range: TextRange,
+    },
+    /// A statement was attributed to the actor under this revision.
+    Speaks { revision: u32, range: TextRange },
+}
+
+impl ActorEvent {
+    pub fn range(&self) -> TextRange {
+        match self {
+            ActorEvent::Revision { range, .. } | ActorEvent::Speaks { range, .. } => *range,
+        }
+    }
+}
+
+/// Revisions and statements of one actor in source order, for answering
+/// which `@actor` block decided how a given line renders.
+pub fn actor_timeline(actors: &ActorLowering, actor_id: ActorId) -> Vec<ActorEvent> {
+    let Some(actor) = actors.actors.get(actor_id.0 as usize) else {
+        return Vec::new();
+    };
+    let mut events = actor
+        .revisions
+        .iter()
+        .map(|revision| ActorEvent::Revision {
+            number: revision.number,
+            state: revision.state.clone(),
+            range: revision.origin,
+        })
+        .chain(actors.speakers.iter().filter_map(|speaker| {
+            (speaker.speaker == SpeakerIdentity::Actor(actor_id)).then_some(ActorEvent::Speaks {
+                revision: speaker.revision?,
+                range: speaker.statement_range,
+            })
+        }))
+        .collect::<Vec<_>>();
+    // An implicit first revision originates at the marker inside the
+    // statement that created it, so ordering by end keeps it first.
+    events.sort_by_key(|event| event.range().end);
+    events
+}
+
+fn speaker_name(speaker: &SpeakerIdentity, actors: &ActorLowering) -> String {
+    match speaker {
+        SpeakerIdentity::Actor(actor_id) => actors.actors[actor_id.0 as usize].primary_name.clone(),
+        SpeakerIdentity::Builtin(builtin) => builtin.display_name().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_text;
+    use crate::semantic::{CharacterPreset, StaticPresetCatalog, lower_actors};
+
+    #[test]
+    fn counts_messages_characters_runs_and_share() {
+        let catalog = StaticPresetCatalog::new(vec![CharacterPreset::new("test::A", ["A"])]);
+        let document = parse_text("> A: 你好呀\n\n> 在吗\n< 在\n- 旁白\n> A: 好的");
+        let report = speaker_report(&document, &lower_actors(&document, &catalog));
+
+        assert_eq!(
+            report
+                .iter()
+                .map(|stats| (
+                    stats.name.as_str(),
+                    stats.messages,
+                    stats.characters,
+                    stats.longest_run
+                ))
+                .collect::<Vec<_>>(),
+            vec![("A", 3, 7, 2), ("Sensei", 1, 1, 1)]
+        );
+        assert_eq!(report[0].share, 7.0 / 8.0);
+        assert_eq!(report[0].first_range.start, 0);
+    }
+
+    #[test]
+    fn speaker_tokens_group_markers_by_name_and_actor() {
+        let catalog =
+            StaticPresetCatalog::new(vec![CharacterPreset::new("test::A", ["A", "Alice"])]);
+        let source = "> A: 一\n> Alice: 二\n> Ailce: 三\n> A: 四";
+        let document = parse_text(source);
+        let tokens = speaker_tokens(&document, &lower_actors(&document, &catalog));
+
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| (token.raw.as_str(), token.resolution, token.ranges.len()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("A", NameResolution::PrimaryName, 2),
+                ("Alice", NameResolution::AdditionalName, 1),
+                ("Ailce", NameResolution::Unresolved, 1),
+            ]
+        );
+        assert_eq!(tokens[0].actor, tokens[1].actor);
+        assert_eq!(tokens[2].ranges[0].start, source.find("Ailce").unwrap());
+    }
+
+    #[test]
+    fn actor_timeline_interleaves_revisions_and_statements() {
+        let catalog = StaticPresetCatalog::new(vec![CharacterPreset::new("test::A", ["A"])]);
+        let source = "> A: 一\n@actor A\ndisplay-name: 阿\n@end\n> A: 二\n< 三";
+        let document = parse_text(source);
+        let actors = lower_actors(&document, &catalog);
+        let events = actor_timeline(&actors, ActorId(0));
+
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| match event {
+                    ActorEvent::Revision { number, state, .. } => {
+                        format!("revision {number} {}", state.display_name)
+                    }
+                    ActorEvent::Speaks { revision, .. } => format!("speaks {revision}"),
+                })
+                .collect::<Vec<_>>(),
+            vec!["revision 0 A", "speaks 0", "revision 1 阿", "speaks 1"]
+        );
+        assert_eq!(events[2].range().start, source.find("@actor").unwrap());
+    }
+}
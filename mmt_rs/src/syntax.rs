@@ -1,3 +1,5 @@
+use std::cell::Cell;
+
 use crate::diag::Diagnostic;
 use crate::inline::InlineMacroSyntax;
 use crate::source::TextRange;
@@ -10,6 +12,17 @@ pub struct SyntaxDocument {
     pub range: TextRange,
 }
 
+impl SyntaxDocument {
+    /// Top-level node whose range contains `offset`. The end is inclusive so a
+    /// cursor right after a node's last character still finds that node.
+    pub fn node_at(&self, offset: usize) -> Option<&SyntaxNode> {
+        let index = self.nodes.partition_point(|node| node.range().end < offset);
+        self.nodes
+            .get(index)
+            .filter(|node| node.range().start <= offset)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 #[serde(tag = "kind", content = "data", rename_all = "snake_case")]
 pub enum SyntaxNode {
@@ -34,9 +47,18 @@ impl SyntaxNode {
             SyntaxNode::Error(node) => node.range,
         }
     }
+
+    /// `@comment` lines and blocks, which render nothing.
+    pub fn is_comment(&self) -> bool {
+        match self {
+            SyntaxNode::DirectiveLine(node) => node.name == "comment",
+            SyntaxNode::DirectiveBlock(node) => node.name == "comment",
+            _ => false,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum StatementKind {
     Left,
@@ -73,9 +95,32 @@ pub struct BodySyntax {
     pub mode: BodyMode,
     pub source: String,
     pub range: TextRange,
+    #[serde(skip_serializing_if = "skips_plain_body_parts")]
     pub parts: Vec<BodyPartSyntax>,
 }
 
+thread_local! {
+    static OMIT_PLAIN_BODY_PARTS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `serialize` with bodies made of one text part serialized without
+/// `parts` when `omit` is set. The parser only produces a lone text part when
+/// it spans the whole body, so `source` and `range` already describe it.
+pub(crate) fn omitting_plain_body_parts<T>(omit: bool, serialize: impl FnOnce() -> T) -> T {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            OMIT_PLAIN_BODY_PARTS.with(|flag| flag.set(self.0));
+        }
+    }
+    let _restore = Restore(OMIT_PLAIN_BODY_PARTS.with(|flag| flag.replace(omit)));
+    serialize()
+}
+
+fn skips_plain_body_parts(parts: &[BodyPartSyntax]) -> bool {
+    OMIT_PLAIN_BODY_PARTS.with(Cell::get) && matches!(parts, [BodyPartSyntax::Text { .. }])
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum BodyMode {
@@ -137,6 +182,7 @@ pub struct LiteralSyntax {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ReplySyntax {
     pub items: Vec<BodySyntax>,
+    pub selected: Option<usize>,
     pub patch: Option<PatchSyntax>,
     pub range: TextRange,
 }
@@ -92,6 +92,7 @@ pub fn scan_typst_overlay_macros(text: &str, origin: TextRange) -> TypstOverlayS
                 ));
                 cursor = end;
             }
+            Err(InlineMacroParseError::NotAMacro) => cursor = start + 2,
         }
     }
 
@@ -218,6 +218,194 @@ fn cli_reports_structured_utf8_source_diagnostics() {
     }
 }
 
+#[test]
+fn cli_reports_speaker_statistics_on_request() {
+    let output_dir = temp_dir("cli-stats");
+    let source_path = output_dir.with_extension("mmt");
+    fs::write(&source_path, "< 早上好\n< 在吗\n- 旁白").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_mmt-compile"))
+        .args(["--input"])
+        .arg(&source_path)
+        .args(["--output-dir"])
+        .arg(&output_dir)
+        .arg("--template-dir")
+        .arg(template_dir())
+        .args(["--clock", "2026-01-02T03:04:05+08:00", "--stats"])
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report["speakers"],
+        serde_json::json!([{
+            "name": "Sensei",
+            "messages": 2,
+            "characters": 5,
+            "longest_run": 2,
+            "share": 1.0
+        }])
+    );
+
+    fs::remove_file(source_path).unwrap();
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn cli_threshold_flags_enable_their_lint() {
+    let output_dir = temp_dir("cli-threshold");
+    let source_path = output_dir.with_extension("mmt");
+    fs::write(&source_path, "< 早上好\n还在吗\n- 旁白").unwrap();
+    let compile = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_mmt-compile"))
+            .args(["--input"])
+            .arg(&source_path)
+            .args(["--output-dir"])
+            .arg(&output_dir)
+            .arg("--template-dir")
+            .arg(template_dir())
+            .args(["--clock", "2026-01-02T03:04:05+08:00"])
+            .args(extra)
+            .output()
+            .unwrap();
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let report = compile(&["--max-continuation-lines", "0"]);
+    assert_eq!(report["diagnostics"][0]["code"], "continuation-lines");
+    assert_eq!(report["diagnostics"][0]["severity"], "warning");
+    let report = compile(&[
+        "--allow",
+        "continuation-lines",
+        "--max-continuation-lines",
+        "0",
+    ]);
+    assert_eq!(report["diagnostics"], serde_json::json!([]));
+
+    fs::remove_file(source_path).unwrap();
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn cli_traces_actor_revisions_and_statements_on_request() {
+    let output_dir = temp_dir("cli-trace");
+    let source_path = output_dir.with_extension("mmt");
+    let manifest_path = output_dir.with_extension("json");
+    fs::write(
+        &manifest_path,
+        r#"{
+            "schema": "mmt-pack.v3",
+            "pack": {"namespace": "trace", "name": "Trace", "version": "1.0.0", "type": "base"},
+            "entities": {"佳代子": {"names": ["佳代子"], "display_name": "佳代子"}}
+        }"#,
+    )
+    .unwrap();
+    fs::write(
+        &source_path,
+        "> 佳代子: 一\n@actor 佳代子\ndisplay-name: 课长\n@end\n> 佳代子: 二",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_mmt-compile"))
+        .args(["--input"])
+        .arg(&source_path)
+        .args(["--output-dir"])
+        .arg(&output_dir)
+        .arg("--template-dir")
+        .arg(template_dir())
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .args(["--clock", "2026-01-02T03:04:05+08:00"])
+        .args(["--trace-actor", "佳代子"])
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let trace = report["trace"].as_array().unwrap();
+    assert_eq!(
+        trace
+            .iter()
+            .map(|event| (
+                event["event"].as_str().unwrap(),
+                event["line"].as_u64().unwrap(),
+                event["revision"].as_u64().unwrap()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            ("revision", 1, 0),
+            ("speaks", 1, 0),
+            ("revision", 2, 1),
+            ("speaks", 5, 1)
+        ]
+    );
+    assert_eq!(trace[2]["display_name"], "课长");
+
+    fs::remove_file(manifest_path).unwrap();
+    fs::remove_file(source_path).unwrap();
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn cli_discovers_project_config_and_lets_arguments_override_it() {
+    let project_dir = temp_dir("cli-config");
+    let output_dir = project_dir.join("out");
+    fs::create_dir_all(project_dir.join("chapters")).unwrap();
+    fs::write(
+        project_dir.join("mmt.toml"),
+        format!(
+            "template-dir = {:?}\n[lints]\nduplicate-message = \"deny\"\n",
+            template_dir().display().to_string()
+        ),
+    )
+    .unwrap();
+    let source_path = project_dir.join("chapters/one.mmt");
+    fs::write(&source_path, "< 收到\n< 收到").unwrap();
+    let compile = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_mmt-compile"))
+            .args(["--input"])
+            .arg(&source_path)
+            .args(["--output-dir"])
+            .arg(&output_dir)
+            .args(["--clock", "2026-01-02T03:04:05+08:00"])
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+
+    let denied = compile(&[]);
+    assert!(!denied.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&denied.stdout).unwrap();
+    assert_eq!(report["diagnostics"][0]["code"], "duplicate-message");
+
+    let allowed = compile(&["--allow", "duplicate-message"]);
+    assert!(
+        allowed.status.success(),
+        "{}",
+        String::from_utf8_lossy(&allowed.stdout)
+    );
+    assert!(output_dir.join("template/lib.typ").is_file());
+
+    fs::write(project_dir.join("mmt.toml"), "tittle = \"x\"").unwrap();
+    let invalid = compile(&[]);
+    let report: serde_json::Value = serde_json::from_slice(&invalid.stdout).unwrap();
+    assert!(
+        report["diagnostics"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("unknown field `tittle`")
+    );
+
+    fs::remove_dir_all(project_dir).unwrap();
+}
+
 #[test]
 fn cli_decodes_pack_avifs_with_dav1d_and_compiles_the_project() {
     let output_dir = temp_dir("cli-avifs");
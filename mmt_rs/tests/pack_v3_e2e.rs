@@ -111,14 +111,12 @@ fn strict_pipeline_generated_typst_compiles_with_typst_015() {
     let mut materializer = FixtureMaterializer {
         typst_path: "../../../mmt_rs/tests/fixtures/pack-v3/materialized.svg".to_string(),
     };
-    let options = EmitOptions {
-        template_import: "../../../mmt_rs/tests/fixtures/typst/mmt-test-lib.typ".to_string(),
-        document_overrides: DocumentOverrides {
+    let options = EmitOptions::default()
+        .with_template_import("../../../mmt_rs/tests/fixtures/typst/mmt-test-lib.typ")
+        .with_document_overrides(DocumentOverrides {
             title: Some("Pack v3 E2E".to_string()),
             ..DocumentOverrides::default()
-        },
-        ..EmitOptions::default()
-    };
+        });
     let source = "> 佳代子: first [:#1:](width: 2em)\n\
                   > _0: continued\n\
                   < sensei side\n\
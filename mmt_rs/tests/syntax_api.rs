@@ -58,12 +58,9 @@ fn public_mode_resolution_api_applies_file_local_directives() {
 
 #[test]
 fn public_actor_lowering_api_captures_statement_revisions() {
-    let catalog = StaticPresetCatalog::new(vec![CharacterPreset {
-        id: "ba::日富美".to_string(),
-        names: vec!["日富美".to_string()],
-        display_name: None,
-        avatar: Some("ba::日富美/avatar/default".to_string()),
-    }]);
+    let catalog = StaticPresetCatalog::new(vec![
+        CharacterPreset::new("ba::日富美", ["日富美"]).with_avatar("ba::日富美/avatar/default"),
+    ]);
     let doc = parse_text(
         "> 日富美: first\n\
          @actor 日富美\n\
@@ -29,10 +29,8 @@ fn real_typst_eval_diagnostic_maps_back_to_typ_directive() {
     let output_path = output_dir.join("generated.pdf");
     let source = "@typ\n#let broken = does-not-exist\n@end\n- ok";
     let registry = PackRegistry::new(Vec::new()).unwrap();
-    let options = EmitOptions {
-        template_import: "../../../mmt_rs/tests/fixtures/typst/mmt-test-lib.typ".to_string(),
-        ..EmitOptions::default()
-    };
+    let options = EmitOptions::default()
+        .with_template_import("../../../mmt_rs/tests/fixtures/typst/mmt-test-lib.typ");
     let compilation = compile_text_strict(source, &registry, &mut NoResources, &options).unwrap();
     fs::write(&generated_path, &compilation.typst.source).unwrap();
 
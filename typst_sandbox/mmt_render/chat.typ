@@ -48,6 +48,7 @@
   side: left,
   avatar: none,
   name: none,
+  nickname: none,
   auto-continued: false,
   continued: auto,
   fill: auto,
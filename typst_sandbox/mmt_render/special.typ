@@ -21,6 +21,7 @@
   fill: rgb("e1edf0"),
   accent: rgb("4b6989"),
   decoration: image("mmt_options.webp"),
+  selected: none,
   ..items,
 ) = pad(left: 4em, box(
   fill: fill,
@@ -46,12 +47,16 @@
   #set text(fill: accent)
   #set align(center)
   #stack(
-    ..items.pos().map(item => pad(x: -4pt, y: -4pt,
+    ..items.pos().enumerate().map(((index, item)) => pad(x: -4pt, y: -4pt,
       shadowed(
         radius: 4pt,
         dy: 3pt,
         color: rgb(89, 85, 101, 50%),
-        block(width: 100%, fill: white, inset: 8pt, radius: 4pt, [#item]),
+        if index == selected {
+          block(width: 100%, fill: accent, inset: 8pt, radius: 4pt, text(fill: white)[#item])
+        } else {
+          block(width: 100%, fill: white, inset: 8pt, radius: 4pt, [#item])
+        },
       ),
     )),
   )